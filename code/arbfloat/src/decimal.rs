@@ -0,0 +1,390 @@
+use crate::{Context, FormatDesc, FormatError, IntStorage, StatusFlags};
+use num_bigint::BigUint;
+use num_traits::{ToPrimitive, Zero};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DecimalKind {
+    Finite { exp: i32 },
+    Infinity,
+    NaN { signaling: bool },
+}
+
+// The radix-10 counterpart of `ArbFloat`: `(-1)^negative * coeff * 10^exp`.
+// Unlike `ArbFloat`, we don't normalize the coefficient. IEEE754 decimal
+// formats tell apart the members of a cohort, e.g. `1.0` (`10 * 10^-1`) and
+// `1.00` (`100 * 10^-2`), so trailing zeros carry information. Zeros need
+// an exponent as well, which is why the sign is kept separately. For NaNs,
+// `coeff` holds the payload.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecimalFloat {
+    kind: DecimalKind,
+    negative: bool,
+    coeff: BigUint,
+}
+
+impl DecimalFloat {
+    pub fn new(kind: DecimalKind, negative: bool, coeff: impl Into<BigUint>) -> Self {
+        Self {
+            kind,
+            negative,
+            coeff: coeff.into(),
+        }
+    }
+}
+
+// The two encodings of the significand that IEEE754 allows for decimal
+// interchange formats: binary integer decimal, which stores it as a plain
+// binary integer, and densely packed decimal, which stores three decimal
+// digits per 10-bit declet.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DecimalEncoding {
+    Bid,
+    Dpd,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct DecimalDesc {
+    // The precision, in decimal digits.
+    digits: u32,
+    // The number of exponent bits in the combination field, on top of the two
+    // that are shared with the leading significand digit.
+    exp_cont_bits: u32,
+}
+
+impl DecimalDesc {
+    pub const DECIMAL32: Self = Self {
+        digits: 7,
+        exp_cont_bits: 6,
+    };
+    pub const DECIMAL64: Self = Self {
+        digits: 16,
+        exp_cont_bits: 8,
+    };
+    pub const DECIMAL128: Self = Self {
+        digits: 34,
+        exp_cont_bits: 12,
+    };
+
+    fn trailing_bits(&self) -> u32 {
+        10 * (self.digits - 1) / 3
+    }
+
+    fn combination_bits(&self) -> u32 {
+        self.exp_cont_bits + 5
+    }
+
+    fn width(&self) -> u32 {
+        1 + self.combination_bits() + self.trailing_bits()
+    }
+
+    fn emax(&self) -> i32 {
+        3 << (self.exp_cont_bits - 1)
+    }
+
+    fn emin(&self) -> i32 {
+        1 - self.emax()
+    }
+
+    // The exponent range of the coefficient's unit digit, i.e. of `exp` in
+    // `DecimalFloat`, is `[-bias, emax - (digits - 1)]`.
+    fn bias(&self) -> i32 {
+        self.emax() + self.digits as i32 - 2
+    }
+
+    fn min_exp(&self) -> i32 {
+        -self.bias()
+    }
+
+    fn max_exp(&self) -> i32 {
+        self.emax() - (self.digits as i32 - 1)
+    }
+
+    fn max_coeff(&self) -> BigUint {
+        BigUint::from(10u32).pow(self.digits) - 1u32
+    }
+
+    // Rounds to the nearest value (as directed by `ctx.rounding`) representable
+    // in the format. Exact results keep their exponent when possible, so the
+    // cohort is preserved.
+    fn round(&self, value: &DecimalFloat, ctx: &mut Context) -> DecimalFloat {
+        let DecimalKind::Finite { exp } = value.kind else {
+            return value.clone();
+        };
+        let digits = count_digits(&value.coeff);
+
+        // Drop digits beyond the precision, and below the smallest exponent.
+        let shift = (digits as i64 - self.digits as i64)
+            .max(self.min_exp() as i64 - exp as i64)
+            .max(0);
+        let (mut coeff, inexact) = round_digits(&value.coeff, shift as u32, value.negative, ctx);
+        let tiny = !value.coeff.is_zero() && exp + digits as i32 - 1 < self.emin();
+        let mut exp = exp + shift as i32;
+        if coeff > self.max_coeff() {
+            // Rounding up carried into an extra digit, which must be a zero.
+            coeff /= 10u32;
+            exp += 1;
+        }
+
+        if inexact {
+            // Decimal formats always detect tininess before rounding.
+            ctx.raise(StatusFlags::INEXACT);
+            if tiny {
+                ctx.raise(StatusFlags::UNDERFLOW);
+            }
+        }
+
+        if exp > self.max_exp() {
+            let pad = (exp - self.max_exp()) as u32;
+            if coeff.is_zero() {
+                exp = self.max_exp();
+            } else if count_digits(&coeff) + pad <= self.digits {
+                // Too large an exponent, but the coefficient has room for
+                // trailing zeros, so this is exact (a "clamp").
+                coeff *= BigUint::from(10u32).pow(pad);
+                exp = self.max_exp();
+            } else {
+                ctx.raise(StatusFlags::OVERFLOW | StatusFlags::INEXACT);
+                if ctx.rounding.overflows_to_infinity(value.negative) {
+                    return DecimalFloat::new(DecimalKind::Infinity, value.negative, 0u32);
+                }
+                coeff = self.max_coeff();
+                exp = self.max_exp();
+            }
+        }
+        DecimalFloat::new(DecimalKind::Finite { exp }, value.negative, coeff)
+    }
+}
+
+fn count_digits(num: &BigUint) -> u32 {
+    if num.is_zero() {
+        0
+    } else {
+        num.to_str_radix(10).len() as u32
+    }
+}
+
+// Divides `num` by `10^shift`, rounding as directed by `ctx.rounding`, and
+// reports whether any nonzero digits were discarded.
+fn round_digits(num: &BigUint, shift: u32, negative: bool, ctx: &Context) -> (BigUint, bool) {
+    if shift == 0 {
+        return (num.clone(), false);
+    }
+    if shift > count_digits(num) {
+        // Spare ourselves a huge power of ten: all of `num` is discarded, and
+        // it's below half a unit.
+        if num.is_zero() {
+            return (BigUint::zero(), false);
+        }
+        let up = ctx.rounding.rounds_up(negative, false, true, false);
+        return (BigUint::from(up as u32), true);
+    }
+    let divisor = BigUint::from(10u32).pow(shift);
+    let (quot, rem) = (num / &divisor, num % &divisor);
+    if rem.is_zero() {
+        return (quot, false);
+    }
+    let half_unit = BigUint::from(5u32) * BigUint::from(10u32).pow(shift - 1);
+    let half = rem >= half_unit;
+    let sticky = !half || rem != half_unit;
+    let odd = quot.bit(0);
+    if ctx.rounding.rounds_up(negative, half, sticky, odd) {
+        (quot + 1u32, true)
+    } else {
+        (quot, true)
+    }
+}
+
+// Decodes a declet into three decimal digits, per IEEE754-2019 table 3.3.
+// The 24 non-canonical declets decode like their canonical counterparts.
+fn decode_declet(declet: u32) -> u32 {
+    let bit = |n: u32| (declet >> n) & 1;
+    let small = |hi: u32, mid: u32, lo: u32| 4 * bit(hi) + 2 * bit(mid) + bit(lo);
+    let large = |lo: u32| 8 + bit(lo);
+    let (d2, d1, d0) = if bit(3) == 0 {
+        (small(9, 8, 7), small(6, 5, 4), small(2, 1, 0))
+    } else {
+        match (bit(2), bit(1), bit(6), bit(5)) {
+            (0, 0, _, _) => (small(9, 8, 7), small(6, 5, 4), large(0)),
+            (0, 1, _, _) => (small(9, 8, 7), large(4), small(6, 5, 0)),
+            (1, 0, _, _) => (large(7), small(6, 5, 4), small(9, 8, 0)),
+            (1, 1, 0, 0) => (large(7), large(4), small(9, 8, 0)),
+            (1, 1, 0, 1) => (large(7), small(9, 8, 4), large(0)),
+            (1, 1, 1, 0) => (small(9, 8, 7), large(4), large(0)),
+            _ => (large(7), large(4), large(0)),
+        }
+    };
+    100 * d2 + 10 * d1 + d0
+}
+
+// Encodes three decimal digits into their canonical declet.
+fn encode_declet(value: u32) -> u32 {
+    let (d2, d1, d0) = (value / 100, value / 10 % 10, value % 10);
+    // `abc`, `def` and `ghi` name the bits of the digits, as in the standard.
+    let (a, b, c) = (d2 >> 2 & 1, d2 >> 1 & 1, d2 & 1);
+    let (d, e, f) = (d1 >> 2 & 1, d1 >> 1 & 1, d1 & 1);
+    let (g, h, i) = (d0 >> 2 & 1, d0 >> 1 & 1, d0 & 1);
+    let bits: [u32; 10] = match (d2 >= 8, d1 >= 8, d0 >= 8) {
+        (false, false, false) => [a, b, c, d, e, f, 0, g, h, i],
+        (false, false, true) => [a, b, c, d, e, f, 1, 0, 0, i],
+        (false, true, false) => [a, b, c, g, h, f, 1, 0, 1, i],
+        (true, false, false) => [g, h, c, d, e, f, 1, 1, 0, i],
+        (true, true, false) => [g, h, c, 0, 0, f, 1, 1, 1, i],
+        (true, false, true) => [d, e, c, 0, 1, f, 1, 1, 1, i],
+        (false, true, true) => [a, b, c, 1, 0, f, 1, 1, 1, i],
+        (true, true, true) => [0, 0, c, 1, 1, f, 1, 1, 1, i],
+    };
+    bits.iter().fold(0, |declet, bit| declet << 1 | bit)
+}
+
+fn decode_declets(trailing: &BigUint, count: u32) -> BigUint {
+    (0..count).rev().fold(BigUint::zero(), |acc, i| {
+        let declet = ((trailing >> (10 * i)) & BigUint::from(0x3ffu32))
+            .to_u32()
+            .unwrap();
+        acc * 1000u32 + decode_declet(declet)
+    })
+}
+
+fn encode_declets(mut num: BigUint, count: u32) -> BigUint {
+    let mut trailing = BigUint::zero();
+    for i in 0..count {
+        let group = (&num % 1000u32).to_u32().unwrap();
+        trailing |= BigUint::from(encode_declet(group)) << (10 * i);
+        num /= 1000u32;
+    }
+    trailing
+}
+
+// Decodes the trailing significand field, which holds everything but the
+// leading digit.
+fn decode_trailing(desc: DecimalDesc, encoding: DecimalEncoding, trailing: &BigUint) -> BigUint {
+    match encoding {
+        DecimalEncoding::Bid => trailing.clone(),
+        DecimalEncoding::Dpd => decode_declets(trailing, desc.trailing_bits() / 10),
+    }
+}
+
+fn encode_trailing(desc: DecimalDesc, encoding: DecimalEncoding, num: BigUint) -> BigUint {
+    match encoding {
+        DecimalEncoding::Bid => num,
+        DecimalEncoding::Dpd => encode_declets(num, desc.trailing_bits() / 10),
+    }
+}
+
+pub fn parse_decimal(
+    desc: DecimalDesc,
+    encoding: DecimalEncoding,
+    storage: impl Into<IntStorage>,
+) -> Result<DecimalFloat, FormatError> {
+    let storage = storage.into();
+    let width = desc.width();
+    if storage.bits() > width as u64 {
+        return Err(FormatError::StorageTooWide { storage, width });
+    }
+    let w = desc.exp_cont_bits;
+    let trailing = &storage & FormatDesc::mask(desc.trailing_bits());
+    let comb = (&storage >> desc.trailing_bits()).to_u32().unwrap() & ((1 << (w + 5)) - 1);
+    let negative = storage.bit(width as u64 - 1);
+
+    // The top five bits of the combination field, G0 to G4, tell apart the
+    // special values, and where the exponent and leading digit live.
+    let top = comb >> w;
+    if top >> 1 == 0b1111 {
+        return Ok(if top & 1 == 0 {
+            DecimalFloat::new(DecimalKind::Infinity, negative, 0u32)
+        } else {
+            let signaling = (comb >> (w - 1)) & 1 != 0;
+            let mut payload = decode_trailing(desc, encoding, &trailing);
+            if count_digits(&payload) >= desc.digits {
+                // Non-canonical payloads are read as zero.
+                payload = BigUint::zero();
+            }
+            DecimalFloat::new(DecimalKind::NaN { signaling }, negative, payload)
+        });
+    }
+
+    let (biased_exp, coeff) = match encoding {
+        DecimalEncoding::Bid => {
+            let (biased_exp, high) = if top >> 3 != 0b11 {
+                (comb >> 3, comb & 0b111)
+            } else {
+                ((comb >> 1) & ((1 << (w + 2)) - 1), 0b1000 | (comb & 1))
+            };
+            let mut coeff = (BigUint::from(high) << desc.trailing_bits()) | trailing;
+            if coeff > desc.max_coeff() {
+                // Non-canonical significands are read as zero.
+                coeff = BigUint::zero();
+            }
+            (biased_exp, coeff)
+        }
+        DecimalEncoding::Dpd => {
+            let (exp_msbs, lead) = if top >> 3 != 0b11 {
+                (top >> 3, top & 0b111)
+            } else {
+                ((top >> 1) & 0b11, 0b1000 | (top & 1))
+            };
+            let biased_exp = (exp_msbs << w) | (comb & ((1 << w) - 1));
+            let coeff = BigUint::from(lead) * BigUint::from(10u32).pow(desc.digits - 1)
+                + decode_trailing(desc, encoding, &trailing);
+            (biased_exp, coeff)
+        }
+    };
+    let exp = biased_exp as i32 - desc.bias();
+    Ok(DecimalFloat::new(
+        DecimalKind::Finite { exp },
+        negative,
+        coeff,
+    ))
+}
+
+pub fn encode_decimal(
+    desc: DecimalDesc,
+    encoding: DecimalEncoding,
+    value: &DecimalFloat,
+    ctx: &mut Context,
+) -> IntStorage {
+    let rounded = desc.round(value, ctx);
+    let w = desc.exp_cont_bits;
+    let (comb, trailing) = match rounded.kind {
+        DecimalKind::Infinity => (0b11110 << w, BigUint::zero()),
+        DecimalKind::NaN { signaling } => {
+            let mut payload = rounded.coeff.clone();
+            if count_digits(&payload) >= desc.digits {
+                payload = BigUint::zero();
+            }
+            let comb = (0b11111 << w) | ((signaling as u32) << (w - 1));
+            (comb, encode_trailing(desc, encoding, payload))
+        }
+        DecimalKind::Finite { exp } => {
+            let biased_exp = (exp + desc.bias()) as u32;
+            let coeff = rounded.coeff;
+            match encoding {
+                DecimalEncoding::Bid => {
+                    let high = (&coeff >> desc.trailing_bits()).to_u32().unwrap();
+                    let trailing = coeff & FormatDesc::mask(desc.trailing_bits());
+                    let comb = if high < 0b1000 {
+                        (biased_exp << 3) | high
+                    } else {
+                        (0b11 << (w + 3)) | (biased_exp << 1) | (high & 1)
+                    };
+                    (comb, trailing)
+                }
+                DecimalEncoding::Dpd => {
+                    let unit = BigUint::from(10u32).pow(desc.digits - 1);
+                    let lead = (&coeff / &unit).to_u32().unwrap();
+                    let exp_msbs = biased_exp >> w;
+                    let top = if lead < 8 {
+                        (exp_msbs << 3) | lead
+                    } else {
+                        0b11000 | (exp_msbs << 1) | (lead & 1)
+                    };
+                    let comb = (top << w) | (biased_exp & ((1 << w) - 1));
+                    (comb, encode_trailing(desc, encoding, coeff % unit))
+                }
+            }
+        }
+    };
+    (IntStorage::from(rounded.negative as u8) << (desc.width() - 1))
+        | (IntStorage::from(comb) << desc.trailing_bits())
+        | trailing
+}
@@ -0,0 +1,114 @@
+use crate::{
+    ArbFloat, Context, FloatKind, FormatDesc, FormatError, IntStorage, OverflowMode, StatusFlags,
+};
+use num_bigint::{BigInt, Sign};
+use num_traits::{One, Signed, Zero};
+
+// A Qm.n fixed-point format: an integer of `int_bits + frac_bits` bits, plus
+// a sign bit when `signed`, scaled by `2^-frac_bits`. Signed formats are
+// two's complement, so they reach down to `-2^int_bits`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct FixedDesc {
+    pub int_bits: u32,
+    pub frac_bits: u32,
+    pub signed: bool,
+}
+
+impl FixedDesc {
+    fn width(&self) -> u32 {
+        self.int_bits + self.frac_bits + self.signed as u32
+    }
+
+    // The range of the underlying integer.
+    fn min(&self) -> BigInt {
+        if self.signed {
+            -(BigInt::one() << (self.width() - 1))
+        } else {
+            BigInt::zero()
+        }
+    }
+
+    fn max(&self) -> BigInt {
+        (BigInt::one() << (self.width() - self.signed as u32)) - 1
+    }
+
+    // The encoding of an integer in the range, or of any integer modulo
+    // `2^width`.
+    fn wrap(&self, int: &BigInt) -> IntStorage {
+        // `BigInt`'s bitwise operations act on two's complement.
+        (int & BigInt::from(FormatDesc::mask(self.width())))
+            .to_biguint()
+            .unwrap()
+    }
+}
+
+pub fn parse_fixed(
+    desc: FixedDesc,
+    storage: impl Into<IntStorage>,
+) -> Result<ArbFloat, FormatError> {
+    let storage = storage.into();
+    let width = desc.width();
+    if storage.bits() > width as u64 {
+        return Err(FormatError::StorageTooWide { storage, width });
+    }
+    if storage.is_zero() {
+        return Ok(ArbFloat::new(FloatKind::Zero, BigInt::one()));
+    }
+    let mut int = BigInt::from_biguint(Sign::Plus, storage);
+    if desc.signed && int.bit(width as u64 - 1) {
+        int -= BigInt::one() << width;
+    }
+    let exp = -(desc.frac_bits as i32);
+    Ok(ArbFloat::new(FloatKind::Regular { exp }, int))
+}
+
+// Rounds `value` to a multiple of `2^-frac_bits` as `ctx` directs. Like
+// IEEE754's conversions to integer formats, values out of range signal
+// invalid rather than overflow. They then saturate under
+// `OverflowMode::Saturating`, and otherwise wrap around, as integer
+// arithmetic in C does. NaNs become zero, and infinities always saturate.
+pub fn encode_fixed(desc: FixedDesc, value: &ArbFloat, ctx: &mut Context) -> IntStorage {
+    let exp = match value.kind {
+        FloatKind::Zero => return IntStorage::zero(),
+        FloatKind::NaN { .. } => {
+            ctx.raise(StatusFlags::INVALID);
+            return IntStorage::zero();
+        }
+        FloatKind::Infinity => {
+            ctx.raise(StatusFlags::INVALID);
+            let bound = if value.num.is_negative() {
+                desc.min()
+            } else {
+                desc.max()
+            };
+            return desc.wrap(&bound);
+        }
+        FloatKind::Regular { exp } => exp,
+    };
+    let negative = value.num.is_negative();
+    let quantum_exp = -(desc.frac_bits as i32);
+    let mant = ArbFloat::round_mantissa(
+        value.num.magnitude(),
+        exp,
+        quantum_exp,
+        negative,
+        ctx.rounding,
+    );
+    let int = if negative {
+        -BigInt::from(mant)
+    } else {
+        BigInt::from(mant)
+    };
+    if int < desc.min() || int > desc.max() {
+        ctx.raise(StatusFlags::INVALID);
+        return match ctx.overflow {
+            OverflowMode::Saturating => desc.wrap(&int.clamp(desc.min(), desc.max())),
+            OverflowMode::NonSaturating => desc.wrap(&int),
+        };
+    }
+    // `num` is odd, so any bits below the quantum make the value inexact.
+    if exp < quantum_exp {
+        ctx.raise(StatusFlags::INEXACT);
+    }
+    desc.wrap(&int)
+}
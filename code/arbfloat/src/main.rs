@@ -1,11 +1,32 @@
-use num_bigint::BigInt;
+mod decimal;
+mod fixed;
+mod multi_double;
+mod mx;
+mod posit;
 
-#[derive(Debug, Copy, Clone)]
+use decimal::{
+    encode_decimal, parse_decimal, DecimalDesc, DecimalEncoding, DecimalFloat, DecimalKind,
+};
+use fixed::{encode_fixed, parse_fixed, FixedDesc};
+use multi_double::{encode_multi_double, parse_multi_double};
+use mx::{MxBlock, MxFormat, BLOCK_SIZE};
+use num_bigint::{BigInt, BigUint};
+use num_traits::{One, Signed, Zero};
+use posit::{encode_posit, parse_posit, PositDesc, Quire};
+use std::fmt;
+use std::hint::black_box;
+use std::ops::{Add, BitOr, BitOrAssign, Mul, Neg, Sub};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 enum FloatKind {
     Regular { exp: i32 },
     Zero,
     Infinity,
-    NaN,
+    NaN { signaling: bool },
+}
+
+impl FloatKind {
+    const QUIET_NAN: Self = Self::NaN { signaling: false };
 }
 
 #[derive(Debug, Clone)]
@@ -21,41 +42,902 @@ impl ArbFloat {
             *exp += adjustment;
             num >>= adjustment;
         }
+        if let FloatKind::NaN { .. } = kind {
+            let adjustment = num.trailing_zeros().unwrap();
+            num >>= adjustment;
+        }
         Self { kind, num }
     }
+
+    // NaNs keep their payload in `num`: the bits of the payload field, from
+    // the most significant down, after a leading one and without trailing
+    // zeros. Payloads are thus left-aligned, as hardware keeps them when
+    // converting between formats: widening appends zeros, and narrowing drops
+    // the lowest bits. The leading one leaves room for the sign, and makes
+    // `±1` the NaN with an empty payload.
+    fn nan(signaling: bool, negative: bool, payload: IntStorage, payload_bits: u32) -> Self {
+        let num = BigInt::from((IntStorage::one() << payload_bits) | payload);
+        Self::new(
+            FloatKind::NaN { signaling },
+            if negative { -num } else { num },
+        )
+    }
+
+    // The payload of a NaN, as it fits in a field of `payload_bits` bits.
+    fn nan_payload(&self, payload_bits: u32) -> IntStorage {
+        let num = self.num.magnitude();
+        let len = num.bits() as u32 - 1;
+        let payload = num ^ (IntStorage::one() << len);
+        if len <= payload_bits {
+            payload << (payload_bits - len)
+        } else {
+            payload >> (len - payload_bits)
+        }
+    }
+
+    // Integers are exact, however large: `int * 2^0`, normalized.
+    fn from_integer(int: BigInt) -> Self {
+        if int.is_zero() {
+            Self::new(FloatKind::Zero, BigInt::one())
+        } else {
+            Self::new(FloatKind::Regular { exp: 0 }, int)
+        }
+    }
+
+    // Rounds to the nearest value (as directed by `ctx.rounding`) representable
+    // in a binary format with `precision` bits of precision, whose normal
+    // numbers have exponents in `[emin, emax]`, and which has subnormals below
+    // that.
+    fn round_to(&self, precision: i32, emin: i32, emax: i32, ctx: &mut Context) -> Self {
+        let mode = ctx.rounding;
+        let FloatKind::Regular { exp } = self.kind else {
+            return self.clone();
+        };
+        let sign = self.num.is_negative();
+        let num = self.num.magnitude();
+
+        // The exponent of the most significant bit, i.e. the IEEE754 exponent
+        // of the value when written as `1.xxx * 2^true_exp`.
+        let true_exp = exp + num.bits() as i32 - 1;
+
+        // The exponent of the least significant bit we can keep. Below `emin`
+        // we stop normalizing and start losing precision gradually
+        // (subnormals), so the quantum stays pinned at that of the smallest
+        // binade.
+        let mut quantum_exp = true_exp.max(emin) - (precision - 1);
+        let inexact = exp < quantum_exp;
+        let mut mant = Self::round_mantissa(num, exp, quantum_exp, sign, mode);
+        // Rounding up may carry into a new binade.
+        if mant.bits() as i32 > precision {
+            mant >>= 1;
+            quantum_exp += 1;
+        }
+
+        let sign_one = BigInt::from(if sign { -1 } else { 1 });
+        // Subnormal results have fewer than `precision` bits, which matters in
+        // tiny formats that have no normal binade below the overflow threshold.
+        if quantum_exp + mant.bits() as i32 - 1 > emax {
+            // Overflow: depending on the direction of rounding, we either
+            // deliver an infinity or the largest finite number.
+            ctx.raise(StatusFlags::OVERFLOW | StatusFlags::INEXACT);
+            return if mode.overflows_to_infinity(sign) {
+                Self::new(FloatKind::Infinity, sign_one)
+            } else {
+                let largest = (BigInt::from(1) << precision) - 1;
+                Self::new(
+                    FloatKind::Regular {
+                        exp: emax - (precision - 1),
+                    },
+                    sign_one * largest,
+                )
+            };
+        }
+        if inexact {
+            // The underflow exception is only signaled for tiny results that
+            // are also inexact.
+            let tiny = match ctx.tininess {
+                TininessMode::BeforeRounding => true_exp < emin,
+                TininessMode::AfterRounding => {
+                    // Round as though the exponent range were unbounded, and
+                    // check whether that carried us up to `2^emin`.
+                    let quantum_exp = true_exp - (precision - 1);
+                    let mant = Self::round_mantissa(num, exp, quantum_exp, sign, mode);
+                    quantum_exp + mant.bits() as i32 - 1 < emin
+                }
+            };
+            ctx.raise(StatusFlags::INEXACT);
+            if tiny {
+                ctx.raise(StatusFlags::UNDERFLOW);
+            }
+        }
+        if mant.is_zero() {
+            return Self::new(FloatKind::Zero, sign_one);
+        }
+        Self::new(
+            FloatKind::Regular { exp: quantum_exp },
+            sign_one * BigInt::from(mant),
+        )
+    }
+
+    // Rounds the magnitude `num * 2^exp` to an integer multiple of
+    // `2^quantum_exp`, and returns that multiple. The result may have one bit
+    // more than expected if rounding up carried into a new binade.
+    fn round_mantissa(
+        num: &BigUint,
+        exp: i32,
+        quantum_exp: i32,
+        negative: bool,
+        mode: RoundingMode,
+    ) -> BigUint {
+        if exp >= quantum_exp {
+            // Already fits in the available precision; no bits to round away.
+            return num << (exp - quantum_exp);
+        }
+        let shift = (quantum_exp - exp) as u64;
+        // `num` is odd, so some nonzero bits are always shifted out here.
+        let half = num.bit(shift - 1);
+        let sticky = shift > 1 && num.trailing_zeros().unwrap() < shift - 1;
+        let mut mant: BigUint = num >> shift;
+        if mode.rounds_up(negative, half, sticky, mant.bit(0)) {
+            mant += 1u32;
+        }
+        mant
+    }
 }
 
-type IntStorage = u64;
+impl ArbFloat {
+    // The NaN that invalid operations deliver, from scratch.
+    fn default_nan(ctx: &Context) -> Self {
+        let sign = match ctx.nan {
+            NanPolicy::X86 => -1,
+            _ => 1,
+        };
+        Self::new(FloatKind::QUIET_NAN, BigInt::from(sign))
+    }
+
+    // What an operation delivers when some of its `operands` are NaNs, as
+    // `ctx.nan` directs. Any signaling NaN among them signals invalid, as
+    // IEEE754 requires.
+    fn propagate_nan(operands: &[&Self], ctx: &mut Context) -> Self {
+        let signaling = |operand: &&&Self| operand.kind == FloatKind::NaN { signaling: true };
+        if operands.iter().any(|operand| signaling(&operand)) {
+            ctx.raise(StatusFlags::INVALID);
+        }
+        let first_nan = || {
+            operands
+                .iter()
+                .find(|operand| matches!(operand.kind, FloatKind::NaN { .. }))
+        };
+        let nan = match ctx.nan {
+            NanPolicy::Ieee | NanPolicy::X86 => first_nan(),
+            NanPolicy::Arm => operands.iter().find(signaling).or_else(first_nan),
+            NanPolicy::ArmDefaultNan | NanPolicy::RiscV => return Self::default_nan(ctx),
+        };
+        let nan = nan.expect("no NaN operand");
+        Self {
+            kind: FloatKind::QUIET_NAN,
+            num: nan.num.clone(),
+        }
+    }
+
+    // Multiplication is exact: the product of two odd integers is odd, so no
+    // precision is lost and no renormalization is required.
+    fn mul_exact(&self, rhs: &Self, ctx: &mut Context) -> Self {
+        let sign = self.num.signum() * rhs.num.signum();
+        match (self.kind, rhs.kind) {
+            (FloatKind::NaN { .. }, _) | (_, FloatKind::NaN { .. }) => {
+                Self::propagate_nan(&[self, rhs], ctx)
+            }
+            (FloatKind::Zero, FloatKind::Infinity) | (FloatKind::Infinity, FloatKind::Zero) => {
+                ctx.raise(StatusFlags::INVALID);
+                Self::default_nan(ctx)
+            }
+            (FloatKind::Infinity, _) | (_, FloatKind::Infinity) => {
+                Self::new(FloatKind::Infinity, sign)
+            }
+            (FloatKind::Zero, _) | (_, FloatKind::Zero) => Self::new(FloatKind::Zero, sign),
+            (FloatKind::Regular { exp: lhs_exp }, FloatKind::Regular { exp: rhs_exp }) => {
+                Self::new(
+                    FloatKind::Regular {
+                        exp: lhs_exp + rhs_exp,
+                    },
+                    &self.num * &rhs.num,
+                )
+            }
+        }
+    }
 
-#[derive(Debug, Copy, Clone)]
+    // Addition is exact as well, but IEEE754 makes the sign of an exact zero
+    // sum depend on the rounding-direction attribute: `x - x` is `+0` under
+    // every mode except roundTowardNegative, where it is `-0`.
+    fn add_exact(&self, rhs: &Self, ctx: &mut Context) -> Self {
+        let exact_zero_sign = || {
+            BigInt::from(if ctx.rounding == RoundingMode::TowardNegative {
+                -1
+            } else {
+                1
+            })
+        };
+        match (self.kind, rhs.kind) {
+            (FloatKind::NaN { .. }, _) | (_, FloatKind::NaN { .. }) => {
+                Self::propagate_nan(&[self, rhs], ctx)
+            }
+            (FloatKind::Infinity, FloatKind::Infinity) => {
+                if self.num == rhs.num {
+                    self.clone()
+                } else {
+                    ctx.raise(StatusFlags::INVALID);
+                    Self::default_nan(ctx)
+                }
+            }
+            (FloatKind::Infinity, _) => self.clone(),
+            (_, FloatKind::Infinity) => rhs.clone(),
+            (FloatKind::Zero, FloatKind::Zero) => {
+                if self.num == rhs.num {
+                    self.clone()
+                } else {
+                    Self::new(FloatKind::Zero, exact_zero_sign())
+                }
+            }
+            (FloatKind::Zero, _) => rhs.clone(),
+            (_, FloatKind::Zero) => self.clone(),
+            (FloatKind::Regular { exp: lhs_exp }, FloatKind::Regular { exp: rhs_exp }) => {
+                // Line both operands up with the smaller exponent, by shifting
+                // the one with the larger exponent to the left.
+                let exp = lhs_exp.min(rhs_exp);
+                let sum = (&self.num << (lhs_exp - exp)) + (&rhs.num << (rhs_exp - exp));
+                if sum.is_zero() {
+                    Self::new(FloatKind::Zero, exact_zero_sign())
+                } else {
+                    Self::new(FloatKind::Regular { exp }, sum)
+                }
+            }
+        }
+    }
+
+    fn sub_exact(&self, rhs: &Self, ctx: &mut Context) -> Self {
+        // Negating a NaN would flip the sign of the NaN delivered.
+        if let FloatKind::NaN { .. } = rhs.kind {
+            return Self::propagate_nan(&[self, rhs], ctx);
+        }
+        self.add_exact(&-rhs, ctx)
+    }
+}
+
+impl Neg for &ArbFloat {
+    type Output = ArbFloat;
+
+    fn neg(self) -> ArbFloat {
+        ArbFloat {
+            kind: self.kind,
+            num: -&self.num,
+        }
+    }
+}
+
+impl Neg for ArbFloat {
+    type Output = ArbFloat;
+
+    fn neg(self) -> ArbFloat {
+        -&self
+    }
+}
+
+// The operators assume roundTiesToEven for the sign of exact zero sums, and
+// discard the status flags; use `add_exact`, `sub_exact` and `mul_exact` to
+// supply a `Context` of your own.
+impl Add for &ArbFloat {
+    type Output = ArbFloat;
+
+    fn add(self, rhs: Self) -> ArbFloat {
+        self.add_exact(rhs, &mut Context::default())
+    }
+}
+
+impl Add for ArbFloat {
+    type Output = ArbFloat;
+
+    fn add(self, rhs: Self) -> ArbFloat {
+        &self + &rhs
+    }
+}
+
+impl Sub for &ArbFloat {
+    type Output = ArbFloat;
+
+    fn sub(self, rhs: Self) -> ArbFloat {
+        self.sub_exact(rhs, &mut Context::default())
+    }
+}
+
+impl Sub for ArbFloat {
+    type Output = ArbFloat;
+
+    fn sub(self, rhs: Self) -> ArbFloat {
+        &self - &rhs
+    }
+}
+
+impl Mul for &ArbFloat {
+    type Output = ArbFloat;
+
+    fn mul(self, rhs: Self) -> ArbFloat {
+        self.mul_exact(rhs, &mut Context::default())
+    }
+}
+
+impl Mul for ArbFloat {
+    type Output = ArbFloat;
+
+    fn mul(self, rhs: Self) -> ArbFloat {
+        &self * &rhs
+    }
+}
+
+// The rounding-direction attributes of IEEE754-2019, section 4.3.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+enum RoundingMode {
+    #[default]
+    TiesToEven,
+    TiesToAway,
+    TowardPositive,
+    TowardNegative,
+    TowardZero,
+}
+
+impl RoundingMode {
+    const ALL: [Self; 5] = [
+        Self::TiesToEven,
+        Self::TiesToAway,
+        Self::TowardPositive,
+        Self::TowardNegative,
+        Self::TowardZero,
+    ];
+
+    // Decides whether an inexact magnitude should be incremented, given the
+    // first discarded bit (`half`), whether any bits below it are set
+    // (`sticky`), and whether the kept part is odd.
+    fn rounds_up(self, negative: bool, half: bool, sticky: bool, odd: bool) -> bool {
+        match self {
+            Self::TiesToEven => half && (sticky || odd),
+            Self::TiesToAway => half,
+            Self::TowardPositive => !negative,
+            Self::TowardNegative => negative,
+            Self::TowardZero => false,
+        }
+    }
+
+    fn overflows_to_infinity(self, negative: bool) -> bool {
+        match self {
+            Self::TiesToEven | Self::TiesToAway => true,
+            Self::TowardPositive => !negative,
+            Self::TowardNegative => negative,
+            Self::TowardZero => false,
+        }
+    }
+}
+
+// IEEE754 lets binary implementations detect tininess (for the purposes of the
+// underflow exception) either before or after rounding. x86 and RISC-V detect
+// it after rounding, whereas ARM and POWER detect it before rounding.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+enum TininessMode {
+    // Tiny if the exact result lies strictly between `±2^emin`.
+    #[default]
+    BeforeRounding,
+    // Tiny if the result, rounded as though the exponent range were unbounded,
+    // lies strictly between `±2^emin`.
+    AfterRounding,
+}
+
+// What to deliver when a result overflows, or when an infinity is converted.
+// Fixed-point formats have neither infinities nor NaNs, and follow integer
+// arithmetic instead.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+enum OverflowMode {
+    // The IEEE754 default: an infinity or the largest finite number, depending
+    // on the rounding mode. Formats without infinities deliver a NaN instead.
+    // Fixed-point results wrap around modulo `2^width`.
+    #[default]
+    NonSaturating,
+    // Always deliver the largest finite number ("satfinite"), even for
+    // infinite operands. Fixed-point results clamp to the nearest bound.
+    Saturating,
+}
+
+// What a conversion to an integer format delivers for NaNs, infinities and
+// finite values out of range. IEEE754 only says to signal invalid, so
+// platforms disagree on the result.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+enum IntegerOverflowMode {
+    // Saturate to the nearest bound, with NaNs converting to zero.
+    #[default]
+    RustAs,
+    // The "integer indefinite" value: the most negative integer for signed
+    // conversions, and all ones for unsigned ones (AVX-512).
+    X86,
+    // Like `RustAs`, which is why Rust gets its semantics for free there.
+    Arm,
+    // Saturate to the nearest bound, with NaNs converting to the largest
+    // integer.
+    RiscV,
+}
+
+// Which NaN an operation delivers when its operands include NaNs, and which
+// one invalid operations deliver. IEEE754 only recommends that the former be
+// one of the input NaNs, so platforms disagree on both.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+enum NanPolicy {
+    // The first NaN operand, quieted. Invalid operations deliver a positive
+    // quiet NaN with an empty payload.
+    #[default]
+    Ieee,
+    // Like `Ieee`, as in SSE and AVX, except that invalid operations deliver
+    // the "real indefinite", which is negative.
+    X86,
+    // The first signaling NaN operand, quieted, or else the first quiet one.
+    // Invalid operations deliver the default NaN of `Ieee`.
+    Arm,
+    // ARM with FPCR.DN set: always the default NaN.
+    ArmDefaultNan,
+    // Always the canonical NaN, which is ARM's default NaN.
+    RiscV,
+}
+
+// The status flags of the five IEEE754 exceptions, section 7. Operations raise
+// them by OR-ing into `Context::flags`, and never clear them.
+#[derive(Copy, Clone, Default, PartialEq, Eq)]
+struct StatusFlags(u8);
+
+impl StatusFlags {
+    const INVALID: Self = Self(1 << 0);
+    const DIVIDE_BY_ZERO: Self = Self(1 << 1);
+    const OVERFLOW: Self = Self(1 << 2);
+    const UNDERFLOW: Self = Self(1 << 3);
+    const INEXACT: Self = Self(1 << 4);
+
+    const NAMES: [(Self, &'static str); 5] = [
+        (Self::INVALID, "INVALID"),
+        (Self::DIVIDE_BY_ZERO, "DIVIDE_BY_ZERO"),
+        (Self::OVERFLOW, "OVERFLOW"),
+        (Self::UNDERFLOW, "UNDERFLOW"),
+        (Self::INEXACT, "INEXACT"),
+    ];
+
+    fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl BitOr for StatusFlags {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for StatusFlags {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+impl fmt::Debug for StatusFlags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names = Self::NAMES
+            .iter()
+            .filter(|(flag, _)| self.contains(*flag))
+            .map(|(_, name)| *name);
+        f.write_str("StatusFlags(")?;
+        for (i, name) in names.enumerate() {
+            if i != 0 {
+                f.write_str(" | ")?;
+            }
+            f.write_str(name)?;
+        }
+        f.write_str(")")
+    }
+}
+
+// The dynamic state threaded through every rounding operation: the attributes
+// that direct it, and the status flags it raises as a side output.
+#[derive(Debug, Clone, Default)]
+struct Context {
+    rounding: RoundingMode,
+    tininess: TininessMode,
+    overflow: OverflowMode,
+    integer_overflow: IntegerOverflowMode,
+    nan: NanPolicy,
+    flags: StatusFlags,
+}
+
+impl Context {
+    fn new(rounding: RoundingMode) -> Self {
+        Self {
+            rounding,
+            ..Self::default()
+        }
+    }
+
+    fn raise(&mut self, flags: StatusFlags) {
+        self.flags |= flags;
+    }
+}
+
+// Encodings are arbitrarily wide bit strings, so that formats such as binary128
+// and binary256 fit as well as the narrow ones.
+type IntStorage = BigUint;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 struct FormatDesc {
     frac_bits: u8,
     exp_bits: u8,
+    // Whether the integer bit is stored in the encoding (right above the
+    // fraction field) rather than implied by the biased exponent, as in the
+    // x87 80-bit extended format.
+    explicit_integer_bit: bool,
+    special_values: SpecialValues,
+    // Overrides the IEEE754 bias of `2^(exp_bits - 1) - 1`.
+    bias_override: Option<i32>,
+    // Without subnormals, a zero biased exponent means zero regardless of the
+    // fraction, and results below the normal range are flushed to zero.
+    subnormals: bool,
+    radix: Radix,
+    word_order: WordOrder,
+    nan_convention: NanConvention,
+}
+
+// The base that the exponent scales the significand by.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Radix {
+    Binary,
+    // As in IBM HFP: there's no hidden digit, the fraction field holds the
+    // whole significand as `0.frac`, and any biased exponent is usable.
+    // Normalized values have a nonzero leading hex digit, but unnormalized
+    // encodings are valid too.
+    Hexadecimal,
+}
+
+impl Radix {
+    fn digit_bits(self) -> i32 {
+        match self {
+            Self::Binary => 1,
+            Self::Hexadecimal => 4,
+        }
+    }
+}
+
+// How the fields are laid out across the 16-bit words of an encoding.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum WordOrder {
+    // Sign, exponent and fraction from most to least significant bit.
+    Natural,
+    // As on the PDP-11 and VAX: the words are in reverse order, so the one
+    // holding the sign and exponent comes first in memory, and a
+    // little-endian load puts it in the lowest bits.
+    Pdp,
+}
+
+// How the most significant bit of a NaN's fraction, the quiet bit, tells quiet
+// NaNs from signaling ones.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum NanConvention {
+    // Set for quiet NaNs, as IEEE754-2008 recommends.
+    Ieee2008,
+    // Set for signaling NaNs, as on PA-RISC and on MIPS before release 6.
+    Legacy,
+}
+
+impl NanConvention {
+    fn signaling_when_set(self) -> bool {
+        self == Self::Legacy
+    }
+}
+
+// How a format spends the encodings with an all-ones biased exponent.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum SpecialValues {
+    // Infinities (zero fraction) and NaNs (nonzero fraction).
+    Ieee,
+    // A normal binade, except for the all-ones fraction which is the only NaN
+    // encoding. There are no infinities. This is OCP FP8 E4M3.
+    FiniteWithNan,
+    // A normal binade: every encoding is a finite number, e.g. OCP FP6 and FP4.
+    Finite,
+    // A normal binade, with no infinities or NaNs. Instead, a set sign bit
+    // with a zero biased exponent is the VAX reserved operand, which faults
+    // when used, and which we read as a NaN. There is no negative zero.
+    ReservedOperand,
+}
+
+impl fmt::Display for FormatDesc {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let widths = format!("e{}m{}", self.exp_bits, self.frac_bits);
+        if let Some((name, _)) = Self::NAMED.iter().find(|(_, desc)| desc == self) {
+            f.write_str(name)
+        } else if *self == Self::ieee(self.frac_bits, self.exp_bits)
+            // Names take precedence over widths in `from_name`, e.g. "e4m3"
+            // is the OCP format, which has no infinities.
+            && Self::NAMED.iter().all(|(name, _)| *name != widths)
+        {
+            f.write_str(&widths)
+        } else {
+            write!(f, "{:?}", self)
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum FormatError {
+    UnknownName(String),
+    NoExponentBits,
+    TooManyExponentBits(u8),
+    NoFractionBits,
+    BiasOutOfRange(i32),
+    // An encoding has bits set above the sign bit of a format `width` bits wide.
+    StorageTooWide { storage: IntStorage, width: u32 },
+    PositTooNarrow(u32),
+    PositScaleOutOfRange { nbits: u32, es: u32 },
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownName(name) => write!(f, "unknown format name {:?}", name),
+            Self::NoExponentBits => f.write_str("a format needs at least one exponent bit"),
+            Self::TooManyExponentBits(exp_bits) => write!(
+                f,
+                "{} exponent bits exceed the maximum of {}",
+                exp_bits,
+                FormatDesc::MAX_EXP_BITS
+            ),
+            Self::NoFractionBits => f.write_str("a format needs at least one fraction bit"),
+            Self::BiasOutOfRange(bias) => write!(f, "exponent bias {} is out of range", bias),
+            Self::StorageTooWide { storage, width } => {
+                write!(f, "{:#x} does not fit in {} bits", storage, width)
+            }
+            Self::PositTooNarrow(nbits) => {
+                write!(f, "a posit needs at least 2 bits, not {}", nbits)
+            }
+            Self::PositScaleOutOfRange { nbits, es } => write!(
+                f,
+                "posits of {} bits with {} exponent bits have too wide a range",
+                nbits, es
+            ),
+        }
+    }
+}
+
+impl std::error::Error for FormatError {}
+
+// Encodings that only formats with an explicit integer bit have. The x87 FPU
+// (since the 80387) rejects all of them except pseudo-denormals as invalid
+// operands.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum EncodingClass {
+    Canonical,
+    // Biased exponent 0 with the integer bit set: read as `1.frac * 2^emin`.
+    PseudoDenormal,
+    // Normal biased exponent with the integer bit clear.
+    Unnormal,
+    // All-ones biased exponent with the integer bit and fraction clear.
+    PseudoInfinity,
+    // All-ones biased exponent with the integer bit clear but not the fraction.
+    PseudoNaN,
 }
 
 impl FormatDesc {
-    const BINARY32: Self = Self {
-        frac_bits: 23,
-        exp_bits: 8,
+    const BINARY16: Self = Self::ieee(10, 5);
+    const BINARY32: Self = Self::ieee(23, 8);
+    const BINARY64: Self = Self::ieee(52, 11);
+    const BINARY128: Self = Self::ieee(112, 15);
+    const BINARY256: Self = Self::ieee(236, 19);
+    const X87_EXTENDED: Self = Self {
+        explicit_integer_bit: true,
+        ..Self::ieee(63, 15)
     };
-    const BINARY64: Self = Self {
-        frac_bits: 52,
-        exp_bits: 11,
+    const E4M3: Self = Self {
+        special_values: SpecialValues::FiniteWithNan,
+        ..Self::ieee(3, 4)
     };
+    const E5M2: Self = Self::ieee(2, 5);
+    // OCP FP6 and FP4, which spend every encoding on finite numbers.
+    const FP6_E2M3: Self = Self::finite(3, 2);
+    const FP6_E3M2: Self = Self::finite(2, 3);
+    const FP4_E2M1: Self = Self::finite(1, 2);
+    const BFLOAT16: Self = Self::ieee(7, 8);
+    const TF32: Self = Self::ieee(10, 8);
+    // IBM System/360 hexadecimal floating point, which flushes exponent
+    // underflows to zero rather than denormalizing.
+    const HFP_SHORT: Self = Self::hfp(24);
+    const HFP_LONG: Self = Self::hfp(56);
+    const VAX_F: Self = Self::vax(23, 8);
+    const VAX_D: Self = Self::vax(55, 8);
+    const VAX_G: Self = Self::vax(52, 11);
+    const VAX_H: Self = Self::vax(112, 15);
+
+    // Known formats by name. The first name listed for a format is its
+    // canonical one, and the rest are aliases.
+    const NAMED: [(&'static str, Self); 30] = [
+        ("binary16", Self::BINARY16),
+        ("binary32", Self::BINARY32),
+        ("binary64", Self::BINARY64),
+        ("binary128", Self::BINARY128),
+        ("binary256", Self::BINARY256),
+        ("x87", Self::X87_EXTENDED),
+        ("e4m3", Self::E4M3),
+        ("e5m2", Self::E5M2),
+        ("fp6_e2m3", Self::FP6_E2M3),
+        ("fp6_e3m2", Self::FP6_E3M2),
+        ("fp4_e2m1", Self::FP4_E2M1),
+        ("bf16", Self::BFLOAT16),
+        ("tf32", Self::TF32),
+        ("hfp_short", Self::HFP_SHORT),
+        ("hfp_long", Self::HFP_LONG),
+        ("vax_f", Self::VAX_F),
+        ("vax_d", Self::VAX_D),
+        ("vax_g", Self::VAX_G),
+        ("vax_h", Self::VAX_H),
+        ("fp16", Self::BINARY16),
+        ("half", Self::BINARY16),
+        ("fp32", Self::BINARY32),
+        ("single", Self::BINARY32),
+        ("fp64", Self::BINARY64),
+        ("double", Self::BINARY64),
+        ("fp128", Self::BINARY128),
+        ("quad", Self::BINARY128),
+        ("bfloat16", Self::BFLOAT16),
+        ("ibm32", Self::HFP_SHORT),
+        ("ibm64", Self::HFP_LONG),
+    ];
+
+    // Keeps the exponents of products and quotients of values in the format
+    // comfortably within an `i32`.
+    const MAX_EXP_BITS: u8 = 24;
+
+    // Looks up a format by name, or by its field widths in "eXmY" notation
+    // (X exponent bits, Y fraction bits), which describes an IEEE754-like
+    // layout.
+    fn from_name(name: &str) -> Result<Self, FormatError> {
+        let lowercase = name.to_ascii_lowercase();
+        if let Some((_, desc)) = Self::NAMED.iter().find(|(known, _)| *known == lowercase) {
+            return Ok(*desc);
+        }
+        let unknown = || FormatError::UnknownName(name.to_string());
+        let (exp_bits, frac_bits) = lowercase
+            .strip_prefix('e')
+            .and_then(|widths| widths.split_once('m'))
+            .ok_or_else(unknown)?;
+        let exp_bits = exp_bits.parse().map_err(|_| unknown())?;
+        let frac_bits = frac_bits.parse().map_err(|_| unknown())?;
+        Self::new(frac_bits, exp_bits)
+    }
+
+    // An IEEE754-like layout, checked for usability.
+    fn new(frac_bits: u8, exp_bits: u8) -> Result<Self, FormatError> {
+        if exp_bits == 0 {
+            return Err(FormatError::NoExponentBits);
+        }
+        if exp_bits > Self::MAX_EXP_BITS {
+            return Err(FormatError::TooManyExponentBits(exp_bits));
+        }
+        if frac_bits == 0 {
+            // Infinities and NaNs would be indistinguishable.
+            return Err(FormatError::NoFractionBits);
+        }
+        Ok(Self::ieee(frac_bits, exp_bits))
+    }
+
+    // Like `new`, unchecked, for use in constants.
+    const fn ieee(frac_bits: u8, exp_bits: u8) -> Self {
+        Self {
+            frac_bits,
+            exp_bits,
+            explicit_integer_bit: false,
+            special_values: SpecialValues::Ieee,
+            bias_override: None,
+            subnormals: true,
+            radix: Radix::Binary,
+            word_order: WordOrder::Natural,
+            nan_convention: NanConvention::Ieee2008,
+        }
+    }
+
+    const fn finite(frac_bits: u8, exp_bits: u8) -> Self {
+        Self {
+            special_values: SpecialValues::Finite,
+            ..Self::ieee(frac_bits, exp_bits)
+        }
+    }
+
+    // `0.frac * 16^(biased_exp - 64)`, with a 7-bit biased exponent.
+    const fn hfp(frac_bits: u8) -> Self {
+        Self {
+            special_values: SpecialValues::Finite,
+            bias_override: Some(64),
+            subnormals: false,
+            radix: Radix::Hexadecimal,
+            ..Self::ieee(frac_bits, 7)
+        }
+    }
+
+    // `0.1frac * 2^(biased_exp - 2^(exp_bits - 1))`, which is an IEEE754-like
+    // hidden bit with a bias of one more.
+    const fn vax(frac_bits: u8, exp_bits: u8) -> Self {
+        Self {
+            special_values: SpecialValues::ReservedOperand,
+            bias_override: Some((1 << (exp_bits - 1)) + 1),
+            subnormals: false,
+            word_order: WordOrder::Pdp,
+            ..Self::ieee(frac_bits, exp_bits)
+        }
+    }
+
+    fn with_bias(self, bias: i32) -> Result<Self, FormatError> {
+        // Same reasoning as `MAX_EXP_BITS`.
+        if bias.unsigned_abs() >= 1 << Self::MAX_EXP_BITS {
+            return Err(FormatError::BiasOutOfRange(bias));
+        }
+        Ok(Self {
+            bias_override: Some(bias),
+            ..self
+        })
+    }
+
+    fn without_subnormals(self) -> Self {
+        Self {
+            subnormals: false,
+            ..self
+        }
+    }
+
+    fn with_legacy_nans(self) -> Self {
+        Self {
+            nan_convention: NanConvention::Legacy,
+            ..self
+        }
+    }
 
     fn precision(&self) -> i32 {
-        self.frac_bits as i32 + 1
+        match self.radix {
+            Radix::Binary => self.frac_bits as i32 + 1,
+            Radix::Hexadecimal => self.frac_bits as i32,
+        }
+    }
+
+    // The precision of the binade with exponent `true_exp`. With a radix of
+    // 16, the leading digit may have up to three leading zero bits, so the
+    // precision wobbles between `precision() - 3` and `precision()`.
+    fn precision_at(&self, true_exp: i32) -> i32 {
+        let digit_bits = self.radix.digit_bits();
+        self.precision() - (digit_bits - 1) + (true_exp - self.emin()).rem_euclid(digit_bits)
+    }
+
+    // Reorders the words of an encoding between `word_order` and the natural
+    // order. Reversing is its own inverse, so this works both ways.
+    fn reorder_words(&self, storage: IntStorage) -> IntStorage {
+        match self.word_order {
+            WordOrder::Natural => storage,
+            WordOrder::Pdp => {
+                let words = (self.sign_shift() + 1).div_ceil(16);
+                (0..words).fold(IntStorage::zero(), |acc, i| {
+                    let word = (&storage >> (16 * i)) & Self::mask(16);
+                    acc | (word << (16 * (words - 1 - i)))
+                })
+            }
+        }
     }
 
     fn mask(bits: u32) -> IntStorage {
-        IntStorage::MAX >> (IntStorage::BITS - bits)
+        (IntStorage::one() << bits) - 1u32
     }
 
     fn frac_mask(&self) -> IntStorage {
         Self::mask(self.frac_bits as u32)
     }
 
-    fn frac_shift(&self) -> IntStorage {
+    fn frac_shift(&self) -> u32 {
         0
     }
 
@@ -63,80 +945,1616 @@ impl FormatDesc {
         Self::mask(self.exp_bits as u32)
     }
 
-    fn biased_exp_shift(&self) -> IntStorage {
-        self.frac_shift() + self.frac_bits as IntStorage
+    fn biased_exp_shift(&self) -> u32 {
+        self.frac_shift() + self.frac_bits as u32 + self.explicit_integer_bit as u32
     }
 
     fn exp_bias(&self) -> i32 {
-        (1 << (self.exp_bits - 1)) - 1
+        self.bias_override.unwrap_or((1 << (self.exp_bits - 1)) - 1)
     }
 
     fn sign_mask(&self) -> IntStorage {
-        1
+        IntStorage::one()
     }
 
-    fn sign_shift(&self) -> IntStorage {
-        self.biased_exp_shift() + self.exp_bits as IntStorage
+    fn sign_shift(&self) -> u32 {
+        self.biased_exp_shift() + self.exp_bits as u32
     }
 
     fn integer_bit(&self) -> IntStorage {
-        1 << self.frac_bits
+        IntStorage::one() << self.frac_bits
+    }
+
+    fn quiet_bit(&self) -> IntStorage {
+        self.integer_bit() >> 1
+    }
+
+    // The width of NaN payloads: the fraction, less the quiet bit if any.
+    fn payload_bits(&self) -> u32 {
+        match self.special_values {
+            SpecialValues::Ieee => self.frac_bits as u32 - 1,
+            SpecialValues::ReservedOperand => self.frac_bits as u32,
+            SpecialValues::FiniteWithNan | SpecialValues::Finite => 0,
+        }
+    }
+
+    // The bits of the significand that are actually stored, i.e. the fraction
+    // along with the integer bit when it is explicit.
+    fn stored_significand_mask(&self) -> IntStorage {
+        Self::mask(self.frac_bits as u32 + self.explicit_integer_bit as u32)
+    }
+
+    // The integer bit as it should be stored for normal numbers, infinities
+    // and NaNs: set when it is explicit, and absent otherwise.
+    fn stored_integer_bit(&self) -> IntStorage {
+        if self.explicit_integer_bit {
+            self.integer_bit()
+        } else {
+            IntStorage::zero()
+        }
+    }
+
+    fn emin(&self) -> i32 {
+        match self.radix {
+            Radix::Binary => 1 - self.exp_bias(),
+            // A leading hex digit of 1 with a zero biased exponent.
+            Radix::Hexadecimal => 4 * (-self.exp_bias() - 1),
+        }
+    }
+
+    fn emax(&self) -> i32 {
+        let max_biased_exp = (1 << self.exp_bits) - 1;
+        let max_biased_exp = match self.special_values {
+            SpecialValues::Ieee => max_biased_exp - 1,
+            SpecialValues::FiniteWithNan
+            | SpecialValues::Finite
+            | SpecialValues::ReservedOperand => max_biased_exp,
+        };
+        match self.radix {
+            Radix::Binary => max_biased_exp - self.exp_bias(),
+            // The top bit of a leading hex digit of F.
+            Radix::Hexadecimal => 4 * (max_biased_exp - self.exp_bias()) - 1,
+        }
+    }
+
+    fn has_infinity(&self) -> bool {
+        self.special_values == SpecialValues::Ieee
+    }
+
+    fn has_nan(&self) -> bool {
+        self.special_values != SpecialValues::Finite
+    }
+
+    fn max_finite(&self, sign: BigInt) -> ArbFloat {
+        let mut max_significand = (BigInt::from(1) << self.precision()) - 1;
+        if self.special_values == SpecialValues::FiniteWithNan {
+            // The all-ones significand is taken by the NaN.
+            max_significand -= 1;
+        }
+        let exp = self.emax() - (self.precision() - 1);
+        ArbFloat::new(FloatKind::Regular { exp }, sign * max_significand)
+    }
+
+    fn round(&self, value: &ArbFloat, ctx: &mut Context) -> ArbFloat {
+        let sign = value.num.signum();
+        match value.kind {
+            FloatKind::NaN { .. } if !self.has_nan() => {
+                // There's nothing sensible to deliver; we pick zero.
+                ctx.raise(StatusFlags::INVALID);
+                return ArbFloat::new(FloatKind::Zero, sign);
+            }
+            FloatKind::Infinity
+                if !self.has_infinity()
+                    && self.has_nan()
+                    && ctx.overflow == OverflowMode::NonSaturating =>
+            {
+                ctx.raise(StatusFlags::INVALID);
+                return ArbFloat::new(FloatKind::QUIET_NAN, sign);
+            }
+            _ => {}
+        }
+
+        // Without subnormals, round as though the exponent range were
+        // unbounded below, and flush tiny results to zero.
+        let emin = if self.subnormals {
+            self.emin()
+        } else {
+            i32::MIN / 2
+        };
+        let precision = match value.kind {
+            FloatKind::Regular { exp } => {
+                let true_exp = exp + value.num.bits() as i32 - 1;
+                // Not `clamp`: in tiny formats, `emin` can exceed `emax`.
+                self.precision_at(true_exp.max(emin).min(self.emax()))
+            }
+            _ => self.precision(),
+        };
+        // Radix-16 encodings need not be normalized, so values below the
+        // normal range that fit exactly with the smallest exponent survive.
+        let unnormalized = matches!(
+            value.kind,
+            FloatKind::Regular { exp }
+                if self.radix == Radix::Hexadecimal
+                    && exp >= -4 * self.exp_bias() - self.frac_bits as i32
+        );
+        let mut rounded = value.round_to(precision, emin, self.emax(), ctx);
+        if let (FloatKind::Regular { exp }, FloatKind::Regular { exp: rounded_exp }) =
+            (value.kind, rounded.kind)
+        {
+            // What gets flushed is what's tiny, before or after rounding.
+            let true_exp = match ctx.tininess {
+                TininessMode::BeforeRounding => exp + value.num.bits() as i32 - 1,
+                TininessMode::AfterRounding => rounded_exp + rounded.num.bits() as i32 - 1,
+            };
+            if !self.subnormals && !unnormalized && true_exp < self.emin() {
+                ctx.raise(StatusFlags::UNDERFLOW | StatusFlags::INEXACT);
+                rounded = ArbFloat::new(FloatKind::Zero, sign.clone());
+            }
+        }
+
+        // `round_to` considers the whole top binade to be finite, but the
+        // all-ones significand may be taken by the NaN. Landing on it is an
+        // overflow too.
+        let all_ones = ArbFloat::new(
+            FloatKind::Regular {
+                exp: self.emax() - (self.precision() - 1),
+            },
+            (BigInt::from(1) << self.precision()) - 1,
+        );
+        if self.special_values == SpecialValues::FiniteWithNan
+            && rounded.kind == all_ones.kind
+            && rounded.num.magnitude() == all_ones.num.magnitude()
+        {
+            ctx.raise(StatusFlags::OVERFLOW | StatusFlags::INEXACT);
+            rounded = if ctx.rounding.overflows_to_infinity(sign.is_negative()) {
+                ArbFloat::new(FloatKind::Infinity, sign.clone())
+            } else {
+                self.max_finite(sign.clone())
+            };
+        }
+
+        if rounded.kind == FloatKind::Infinity {
+            return self.infinity(sign, ctx);
+        }
+        rounded
+    }
+
+    // Infinities that the format can't hold, or that we were asked to
+    // saturate, become NaNs or the largest finite number.
+    fn infinity(&self, sign: BigInt, ctx: &Context) -> ArbFloat {
+        if self.has_infinity() && ctx.overflow == OverflowMode::NonSaturating {
+            ArbFloat::new(FloatKind::Infinity, sign)
+        } else if self.has_nan() && ctx.overflow == OverflowMode::NonSaturating {
+            ArbFloat::new(FloatKind::QUIET_NAN, sign)
+        } else {
+            self.max_finite(sign)
+        }
+    }
+
+    // Rounds the result of an operation. Unlike converting an infinity, an
+    // exact infinite result, e.g. of a division by zero, is no invalid
+    // operation, so it's delivered as the format does on overflow, without
+    // raising anything more.
+    fn round_result(&self, value: &ArbFloat, ctx: &mut Context) -> ArbFloat {
+        if value.kind == FloatKind::Infinity {
+            return self.infinity(value.num.clone(), ctx);
+        }
+        self.round(value, ctx)
+    }
+}
+
+fn classify(desc: FormatDesc, storage: impl Into<IntStorage>) -> EncodingClass {
+    let storage = storage.into();
+    if !desc.explicit_integer_bit {
+        return EncodingClass::Canonical;
+    }
+    let frac = (&storage >> desc.frac_shift()) & desc.frac_mask();
+    let biased_exp = (&storage >> desc.biased_exp_shift()) & desc.biased_exp_mask();
+    let integer = !((&storage >> desc.frac_shift()) & desc.integer_bit()).is_zero();
+    if biased_exp.is_zero() {
+        if integer {
+            EncodingClass::PseudoDenormal
+        } else {
+            EncodingClass::Canonical
+        }
+    } else if integer {
+        EncodingClass::Canonical
+    } else if biased_exp != desc.biased_exp_mask() {
+        EncodingClass::Unnormal
+    } else if frac.is_zero() {
+        EncodingClass::PseudoInfinity
+    } else {
+        EncodingClass::PseudoNaN
     }
 }
 
-fn parse(desc: FormatDesc, storage: IntStorage) -> ArbFloat {
-    let frac = (storage >> desc.frac_shift()) & desc.frac_mask();
-    let biased_exp = (storage >> desc.biased_exp_shift()) & desc.biased_exp_mask();
-    let sign = ((storage >> desc.sign_shift()) & desc.sign_mask()) != 0;
+fn parse(desc: FormatDesc, storage: impl Into<IntStorage>) -> Result<ArbFloat, FormatError> {
+    let storage = storage.into();
+    let width = desc.sign_shift() + 1;
+    if storage.bits() > width as u64 {
+        return Err(FormatError::StorageTooWide { storage, width });
+    }
+    let storage = desc.reorder_words(storage);
+    let frac = (&storage >> desc.frac_shift()) & desc.frac_mask();
+    let biased_exp = (&storage >> desc.biased_exp_shift()) & desc.biased_exp_mask();
+    let sign = !((&storage >> desc.sign_shift()) & desc.sign_mask()).is_zero();
+    let class = classify(desc, storage);
+    let mut num = BigInt::from(if sign { -1 } else { 1 });
+
+    if desc.radix == Radix::Hexadecimal {
+        // Every encoding is `0.frac * 16^(biased_exp - bias)`, where `frac`
+        // has `frac_bits` bits, normalized or not.
+        let exp =
+            4 * (i32::try_from(&biased_exp).unwrap() - desc.exp_bias()) - desc.frac_bits as i32;
+        let kind = if frac.is_zero() {
+            FloatKind::Zero
+        } else {
+            num *= BigInt::from(frac);
+            FloatKind::Regular { exp }
+        };
+        return Ok(ArbFloat::new(kind, num));
+    }
 
     // We add the precision to the bias to be able to interpret the fraction
     // field as an integer rather than as a fixed-point number in [1, 2).
     // We've essentially moved the radix point to the right by multiplying
     // the fraction by `2^(precision - 1)`, so we compensate by subtracting
     // `precision - 1` from the exponent.
-    let exp = biased_exp as i32 - (desc.exp_bias() + desc.precision() - 1);
-    let mut num = BigInt::from(if sign { -1 } else { 1 });
-    let kind = if biased_exp == desc.biased_exp_mask() {
-        if frac == 0 {
+    let exp = i32::try_from(&biased_exp).unwrap() - (desc.exp_bias() + desc.precision() - 1);
+    let kind = if matches!(
+        class,
+        EncodingClass::Unnormal | EncodingClass::PseudoInfinity | EncodingClass::PseudoNaN
+    ) {
+        // Like the x87 FPU, treat these as invalid operands, which behave as
+        // signaling NaNs.
+        return Ok(ArbFloat::nan(true, sign, IntStorage::zero(), 0));
+    } else if class == EncodingClass::PseudoDenormal {
+        // The explicit integer bit is honored, but the exponent is still that
+        // of the subnormals.
+        num *= BigInt::from(frac | desc.integer_bit());
+        FloatKind::Regular { exp: exp + 1 }
+    } else if desc.special_values == SpecialValues::FiniteWithNan
+        && biased_exp == desc.biased_exp_mask()
+        && frac == desc.frac_mask()
+    {
+        FloatKind::QUIET_NAN
+    } else if desc.special_values == SpecialValues::Ieee && biased_exp == desc.biased_exp_mask() {
+        if frac.is_zero() {
             FloatKind::Infinity
         } else {
-            FloatKind::NaN
+            let quiet_bit_set = !(&frac & desc.quiet_bit()).is_zero();
+            let signaling = quiet_bit_set == desc.nan_convention.signaling_when_set();
+            let payload = frac & FormatDesc::mask(desc.payload_bits());
+            return Ok(ArbFloat::nan(signaling, sign, payload, desc.payload_bits()));
         }
-    } else if biased_exp == 0 {
-        if frac == 0 {
+    } else if desc.special_values == SpecialValues::ReservedOperand && sign && biased_exp.is_zero()
+    {
+        // Using the reserved operand faults, so it's as signaling as NaNs get.
+        return Ok(ArbFloat::nan(true, sign, frac, desc.payload_bits()));
+    } else if biased_exp.is_zero() {
+        if frac.is_zero() || !desc.subnormals {
             FloatKind::Zero
         } else {
             // Subnormals. Multiply by `frac` here only to preserve the sign of zeros.
-            num *= frac;
+            num *= BigInt::from(frac);
             FloatKind::Regular { exp: exp + 1 }
         }
     } else {
-        num *= frac | desc.integer_bit();
+        num *= BigInt::from(frac | desc.integer_bit());
         FloatKind::Regular { exp }
     };
-    ArbFloat::new(kind, num)
+    Ok(ArbFloat::new(kind, num))
+}
+
+fn encode(desc: FormatDesc, value: &ArbFloat, ctx: &mut Context) -> IntStorage {
+    let rounded = desc.round(value, ctx);
+    let sign = match rounded.kind {
+        FloatKind::Zero if desc.special_values == SpecialValues::ReservedOperand => false,
+        FloatKind::NaN { .. } if desc.special_values == SpecialValues::ReservedOperand => true,
+        _ => rounded.num.is_negative(),
+    };
+    let (biased_exp, frac) = match rounded.kind {
+        FloatKind::Zero => (IntStorage::zero(), IntStorage::zero()),
+        FloatKind::NaN { .. } if desc.special_values == SpecialValues::ReservedOperand => {
+            (IntStorage::zero(), rounded.nan_payload(desc.payload_bits()))
+        }
+        FloatKind::Infinity => (desc.biased_exp_mask(), desc.stored_integer_bit()),
+        FloatKind::NaN { .. } if desc.special_values == SpecialValues::FiniteWithNan => {
+            (desc.biased_exp_mask(), desc.frac_mask())
+        }
+        FloatKind::NaN { signaling } => {
+            let payload = rounded.nan_payload(desc.payload_bits());
+            let frac = if signaling == desc.nan_convention.signaling_when_set() {
+                payload | desc.quiet_bit()
+            } else if !payload.is_zero() {
+                payload
+            } else {
+                // With the quiet bit clear, it takes a payload to tell a NaN
+                // from an infinity. Like `__builtin_nans("")`, we set the top
+                // payload bit of signaling NaNs, and like legacy MIPS, every
+                // payload bit of quiet ones. Without payload bits, there's
+                // only the other kind of NaN left.
+                let frac = match desc.nan_convention {
+                    NanConvention::Ieee2008 => desc.quiet_bit() >> 1,
+                    NanConvention::Legacy => FormatDesc::mask(desc.payload_bits()),
+                };
+                if frac.is_zero() {
+                    desc.quiet_bit()
+                } else {
+                    frac
+                }
+            };
+            (desc.biased_exp_mask(), desc.stored_integer_bit() | frac)
+        }
+        FloatKind::Regular { exp } => {
+            // The value is now exactly representable, so all that remains is
+            // to line its bits up with the fraction field.
+            let num = rounded.num.magnitude();
+            let true_exp = exp + num.bits() as i32 - 1;
+            if desc.radix == Radix::Hexadecimal {
+                // Pick the biased exponent that puts the top bit in the
+                // leading hex digit, or the smallest one if the value is
+                // below the normalized range.
+                let biased_exp = (true_exp.max(desc.emin()) - desc.emin()) / 4;
+                let quantum_exp = 4 * (biased_exp - desc.exp_bias()) - desc.frac_bits as i32;
+                (
+                    IntStorage::from(biased_exp as u32),
+                    num << (exp - quantum_exp),
+                )
+            } else {
+                let quantum_exp = true_exp.max(desc.emin()) - (desc.precision() - 1);
+                let mant = num << (exp - quantum_exp);
+                if true_exp < desc.emin() {
+                    // Subnormal: the biased exponent is 0 and the integer bit is
+                    // clear, so the mantissa is stored as-is.
+                    (IntStorage::zero(), mant)
+                } else {
+                    let biased_exp = IntStorage::from((true_exp + desc.exp_bias()) as u32);
+                    (biased_exp, mant & desc.stored_significand_mask())
+                }
+            }
+        }
+    };
+    desc.reorder_words(
+        (IntStorage::from(sign) << desc.sign_shift())
+            | (biased_exp << desc.biased_exp_shift())
+            | (frac << desc.frac_shift()),
+    )
+}
+
+// Unlike multiplication and addition, division can't be carried out exactly in
+// our dyadic representation (think 1/3), so we round it straight into `desc`.
+fn div_rounded(lhs: &ArbFloat, rhs: &ArbFloat, desc: FormatDesc, ctx: &mut Context) -> ArbFloat {
+    let sign = lhs.num.signum() * rhs.num.signum();
+    let quot = match (lhs.kind, rhs.kind) {
+        (FloatKind::NaN { .. }, _) | (_, FloatKind::NaN { .. }) => {
+            ArbFloat::propagate_nan(&[lhs, rhs], ctx)
+        }
+        (FloatKind::Infinity, FloatKind::Infinity) | (FloatKind::Zero, FloatKind::Zero) => {
+            ctx.raise(StatusFlags::INVALID);
+            ArbFloat::default_nan(ctx)
+        }
+        (FloatKind::Infinity, _) => ArbFloat::new(FloatKind::Infinity, sign),
+        (_, FloatKind::Zero) => {
+            ctx.raise(StatusFlags::DIVIDE_BY_ZERO);
+            ArbFloat::new(FloatKind::Infinity, sign)
+        }
+        (FloatKind::Zero, _) | (_, FloatKind::Infinity) => ArbFloat::new(FloatKind::Zero, sign),
+        (FloatKind::Regular { exp: lhs_exp }, FloatKind::Regular { exp: rhs_exp }) => {
+            let lhs_num = lhs.num.magnitude();
+            let rhs_num = rhs.num.magnitude();
+
+            // Scale the dividend up so that the integer quotient has at least
+            // two more bits than the destination's precision: enough for the
+            // rounding bit, with room to spare below it for the sticky bit.
+            let shift = desc.precision() + 2 + rhs_num.bits() as i32;
+            let dividend = lhs_num << shift;
+            let quot = &dividend / rhs_num;
+            let sticky = !(&dividend % rhs_num).is_zero();
+
+            // Appending the sticky bit as a new least significant bit keeps
+            // the quotient on the same side of every rounding boundary that
+            // matters, so rounding it gives the correctly rounded result.
+            let num = (quot << 1) | BigUint::from(sticky as u32);
+            let exp = lhs_exp - rhs_exp - shift - 1;
+            ArbFloat::new(FloatKind::Regular { exp }, sign * BigInt::from(num))
+        }
+    };
+    desc.round_result(&quot, ctx)
+}
+
+fn sqrt_rounded(value: &ArbFloat, desc: FormatDesc, ctx: &mut Context) -> ArbFloat {
+    let root = match value.kind {
+        FloatKind::NaN { .. } => ArbFloat::propagate_nan(&[value], ctx),
+        // sqrt(-0) is -0, and sqrt(+inf) is +inf.
+        FloatKind::Zero => value.clone(),
+        _ if value.num.is_negative() => {
+            ctx.raise(StatusFlags::INVALID);
+            ArbFloat::default_nan(ctx)
+        }
+        FloatKind::Infinity => value.clone(),
+        FloatKind::Regular { exp } => {
+            let num = value.num.magnitude();
+
+            // Scale the radicand up so that its integer square root has at
+            // least two more bits than the destination's precision, and so
+            // that the exponent becomes even and can be halved exactly.
+            let mut shift = (2 * (desc.precision() + 2) - num.bits() as i32).max(0);
+            if (exp - shift) % 2 != 0 {
+                shift += 1;
+            }
+            let radicand = num << shift;
+            let root = radicand.sqrt();
+            let sticky = &root * &root != radicand;
+
+            // As with division, the sticky bit becomes a new least
+            // significant bit, below the rounding bit.
+            let num = (root << 1) | BigUint::from(sticky as u32);
+            let exp = (exp - shift) / 2 - 1;
+            ArbFloat::new(FloatKind::Regular { exp }, BigInt::from(num))
+        }
+    };
+    desc.round_result(&root, ctx)
+}
+
+// IEEE754's convertFormat. Unlike `encode`, which stores NaNs as they are,
+// this is an operation: signaling NaNs signal invalid, and NaNs come out as
+// `ctx.nan` directs, e.g. canonical on RISC-V.
+fn convert_format(value: &ArbFloat, desc: FormatDesc, ctx: &mut Context) -> ArbFloat {
+    match value.kind {
+        FloatKind::NaN { .. } => desc.round(&ArbFloat::propagate_nan(&[value], ctx), ctx),
+        _ => desc.round(value, ctx),
+    }
+}
+
+// Since `ArbFloat` addition and multiplication are exact, a fused multiply-add
+// is just the two of them followed by a single rounding.
+fn fma(
+    lhs: &ArbFloat,
+    rhs: &ArbFloat,
+    addend: &ArbFloat,
+    desc: FormatDesc,
+    ctx: &mut Context,
+) -> ArbFloat {
+    // A NaN addend takes precedence over the invalid 0 * inf product, since
+    // the latter doesn't stem from an input NaN. Whether invalid is signaled
+    // in this case is implementation-defined; like ARM and RISC-V, we do,
+    // except on x86, which doesn't.
+    if let FloatKind::NaN { signaling } = addend.kind {
+        let invalid_product = matches!(
+            (lhs.kind, rhs.kind),
+            (FloatKind::Zero, FloatKind::Infinity) | (FloatKind::Infinity, FloatKind::Zero)
+        );
+        if invalid_product && ctx.nan != NanPolicy::X86 {
+            ctx.raise(StatusFlags::INVALID);
+        }
+        // Unless the addend is signaling, ARM delivers the default NaN for
+        // the invalid product instead.
+        if invalid_product && !signaling && ctx.nan == NanPolicy::Arm {
+            return desc.round(&ArbFloat::default_nan(ctx), ctx);
+        }
+        // Among NaN operands, the addend comes first, as in ARM's FMADD,
+        // except on x86, which looks at the multiplicands first.
+        let operands = match ctx.nan {
+            NanPolicy::X86 => [lhs, rhs, addend],
+            _ => [addend, lhs, rhs],
+        };
+        return desc.round(&ArbFloat::propagate_nan(&operands, ctx), ctx);
+    }
+    let product = lhs.mul_exact(rhs, ctx);
+    // Adding with the rounding mode in hand also takes care of the sign of
+    // exact zero results, e.g. `(+0 * -1) + +0`, or `(1 * 1) + -1`.
+    desc.round_result(&product.add_exact(addend, ctx), ctx)
+}
+
+// Converts to an integer of `width` bits, two's complement if `signed`,
+// rounding as `ctx` directs (`as` in Rust always rounds toward zero). Like
+// the convertToIntegerExact operations and hardware instructions, we signal
+// inexact for non-integral values. Results out of range signal invalid
+// instead, and are then delivered per `ctx.integer_overflow`.
+fn convert_to_integer(value: &ArbFloat, width: u32, signed: bool, ctx: &mut Context) -> BigInt {
+    // A zero-width integer only holds 0, signed or not.
+    let (min, max) = if signed && width > 0 {
+        (
+            -(BigInt::one() << (width - 1)),
+            (BigInt::one() << (width - 1)) - 1,
+        )
+    } else {
+        (BigInt::zero(), (BigInt::one() << width) - 1)
+    };
+    let negative = value.num.is_negative();
+    let int = match value.kind {
+        FloatKind::Zero => return BigInt::zero(),
+        FloatKind::NaN { .. } | FloatKind::Infinity => None,
+        // At `2^width` and beyond, nothing is in range. Checking for that
+        // first spares us shifting the value into a huge integer.
+        FloatKind::Regular { exp } if exp as i64 + value.num.bits() as i64 > width as i64 => None,
+        FloatKind::Regular { exp } => {
+            let mant =
+                ArbFloat::round_mantissa(value.num.magnitude(), exp, 0, negative, ctx.rounding);
+            let int = if negative {
+                -BigInt::from(mant)
+            } else {
+                BigInt::from(mant)
+            };
+            Some(int).filter(|int| *int >= min && *int <= max)
+        }
+    };
+    if let Some(int) = int {
+        // `num` is odd, so a negative exponent means a fractional part.
+        if matches!(value.kind, FloatKind::Regular { exp } if exp < 0) {
+            ctx.raise(StatusFlags::INEXACT);
+        }
+        return int;
+    }
+
+    ctx.raise(StatusFlags::INVALID);
+    let nan = matches!(value.kind, FloatKind::NaN { .. });
+    match ctx.integer_overflow {
+        IntegerOverflowMode::X86 if signed => min,
+        IntegerOverflowMode::X86 => max,
+        IntegerOverflowMode::RustAs | IntegerOverflowMode::Arm if nan => BigInt::zero(),
+        IntegerOverflowMode::RiscV if nan => max,
+        _ if negative => min,
+        _ => max,
+    }
+}
+
+// IEEE754's convertFromInt: integers too wide for the precision round as `ctx`
+// directs, and those too large for the format overflow like any other value.
+fn convert_from_int(int: BigInt, desc: FormatDesc, ctx: &mut Context) -> ArbFloat {
+    desc.round(&ArbFloat::from_integer(int), ctx)
 }
 
 fn print_examples() {
-    println!("{:?}", parse(FormatDesc::BINARY32, 0x8000_0000)); // -0f32
-    println!("{:?}", parse(FormatDesc::BINARY32, 0x7F80_0000)); // f32::INFINITY
-    println!("{:?}", parse(FormatDesc::BINARY32, 0x7FC0_0000)); // f32::NAN
-    println!("{:?}", parse(FormatDesc::BINARY32, 0x3F80_0000)); // 1f32
-    println!("{:?}", parse(FormatDesc::BINARY64, 0x3FF0_0000_0000_0000)); // 1f64
+    println!("{:?}", parse(FormatDesc::BINARY32, 0x8000_0000u32).unwrap()); // -0f32
+    println!("{:?}", parse(FormatDesc::BINARY32, 0x7F80_0000u32).unwrap()); // f32::INFINITY
+    println!("{:?}", parse(FormatDesc::BINARY32, 0x7FC0_0000u32).unwrap()); // f32::NAN
+    println!("{:?}", parse(FormatDesc::BINARY32, 0x3F80_0000u32).unwrap()); // 1f32
+    println!(
+        "{:?}",
+        parse(FormatDesc::BINARY64, 0x3FF0_0000_0000_0000u64).unwrap()
+    ); // 1f64
 }
 
 fn print_binary3() {
-    const BINARY3: FormatDesc = FormatDesc {
-        frac_bits: 1,
-        exp_bits: 1,
+    const BINARY3: FormatDesc = FormatDesc::ieee(1, 1);
+    for x in 0..8u8 {
+        println!("{:?}", parse(BINARY3, x).unwrap());
+    }
+}
+
+fn print_rounding() {
+    // 1 + 2^-24 is exactly halfway between 1 and the next f32.
+    let value = ArbFloat::new(FloatKind::Regular { exp: -24 }, BigInt::from((1 << 24) + 1));
+    for mode in RoundingMode::ALL {
+        println!(
+            "{:?}: {:#x}",
+            mode,
+            encode(FormatDesc::BINARY32, &value, &mut Context::new(mode))
+        );
+    }
+}
+
+fn print_multiplication() {
+    // Round the exact product back down to single precision, and compare it
+    // to what the hardware multiplier computed.
+    for (x, y) in [
+        (1.1f32, 3.3f32),
+        (-0.0, 5.0),
+        (f32::MAX, 2.0),
+        (1e-30, 1e-10),
+    ] {
+        let product = parse(FormatDesc::BINARY32, x.to_bits()).unwrap()
+            * parse(FormatDesc::BINARY32, y.to_bits()).unwrap();
+        let encoded = encode(FormatDesc::BINARY32, &product, &mut Context::default());
+        println!(
+            "{:?} * {:?} = {:?} ({:#x} vs. {:#x})",
+            x,
+            y,
+            product,
+            encoded,
+            (x * y).to_bits()
+        );
+    }
+}
+
+fn print_addition() {
+    for (x, y) in [(0.1f32, 0.2f32), (1.0, -1.0), (-0.0, -0.0), (1e30, 1e-30)] {
+        let sum = parse(FormatDesc::BINARY32, x.to_bits()).unwrap()
+            + parse(FormatDesc::BINARY32, y.to_bits()).unwrap();
+        let encoded = encode(FormatDesc::BINARY32, &sum, &mut Context::default());
+        println!(
+            "{:?} + {:?} = {:?} ({:#x} vs. {:#x})",
+            x,
+            y,
+            sum,
+            encoded,
+            (x + y).to_bits()
+        );
+    }
+
+    // Exact cancellation yields -0 only when rounding toward negative.
+    let one = parse(FormatDesc::BINARY32, 1f32.to_bits()).unwrap();
+    for mode in RoundingMode::ALL {
+        println!(
+            "{:?}: 1 - 1 = {:?}",
+            mode,
+            one.sub_exact(&one, &mut Context::new(mode))
+        );
+    }
+}
+
+fn print_division() {
+    for (x, y) in [(1f32, 3f32), (-1.0, 0.0), (0.0, -2.0), (1e-38, 3e3)] {
+        let mut ctx = Context::default();
+        let quot = div_rounded(
+            &parse(FormatDesc::BINARY32, x.to_bits()).unwrap(),
+            &parse(FormatDesc::BINARY32, y.to_bits()).unwrap(),
+            FormatDesc::BINARY32,
+            &mut ctx,
+        );
+        let encoded = encode(FormatDesc::BINARY32, &quot, &mut ctx);
+        println!(
+            "{:?} / {:?} = {:?} ({:#x} vs. {:#x}) {:?}",
+            x,
+            y,
+            quot,
+            encoded,
+            (x / y).to_bits(),
+            ctx.flags
+        );
+    }
+    // Dividing by zero is no invalid operation, even in formats without
+    // infinities.
+    let one = ArbFloat::from_integer(BigInt::one());
+    let zero = ArbFloat::from_integer(BigInt::zero());
+    for desc in [FormatDesc::E4M3, FormatDesc::FP4_E2M1] {
+        let mut ctx = Context::default();
+        let quot = div_rounded(&one, &zero, desc, &mut ctx);
+        let encoded = encode(desc, &quot, &mut ctx);
+        println!("1 / 0 in {}: {:#x} {:?}", desc, encoded, ctx.flags);
+    }
+}
+
+fn print_sqrt() {
+    for x in [2f32, 0.25, -0.0, 9.0, 1e-45] {
+        let root = sqrt_rounded(
+            &parse(FormatDesc::BINARY32, x.to_bits()).unwrap(),
+            FormatDesc::BINARY32,
+            &mut Context::default(),
+        );
+        let encoded = encode(FormatDesc::BINARY32, &root, &mut Context::default());
+        println!(
+            "sqrt({:?}) = {:?} ({:#x} vs. {:#x})",
+            x,
+            root,
+            encoded,
+            x.sqrt().to_bits()
+        );
+    }
+}
+
+fn print_fma() {
+    // The fused result differs from the separately rounded one whenever the
+    // product is inexact.
+    for (x, y, z) in [
+        (0.1f32, 10f32, -1f32),
+        (1.0, -0.0, 0.0),
+        (3.0, 1e-40, 1e-45),
+    ] {
+        let result = fma(
+            &parse(FormatDesc::BINARY32, x.to_bits()).unwrap(),
+            &parse(FormatDesc::BINARY32, y.to_bits()).unwrap(),
+            &parse(FormatDesc::BINARY32, z.to_bits()).unwrap(),
+            FormatDesc::BINARY32,
+            &mut Context::default(),
+        );
+        let encoded = encode(FormatDesc::BINARY32, &result, &mut Context::default());
+        println!(
+            "fma({:?}, {:?}, {:?}) = {:?} ({:#x} vs. {:#x})",
+            x,
+            y,
+            z,
+            result,
+            encoded,
+            x.mul_add(y, z).to_bits()
+        );
+    }
+}
+
+fn print_wide_formats() {
+    // 1/3 correctly rounded to quad and octuple precision.
+    let one = parse(FormatDesc::BINARY32, 1f32.to_bits()).unwrap();
+    let three = parse(FormatDesc::BINARY32, 3f32.to_bits()).unwrap();
+    for desc in [FormatDesc::BINARY128, FormatDesc::BINARY256] {
+        let mut ctx = Context::default();
+        let third = div_rounded(&one, &three, desc, &mut ctx);
+        let encoded = encode(desc, &third, &mut ctx);
+        println!("{:#x} {:?}", encoded, parse(desc, encoded.clone()).unwrap());
+    }
+}
+
+fn print_x87() {
+    for storage in [
+        0x3FFF_8000_0000_0000_0000u128, // 1.0
+        0x0000_0000_0000_0000_0001,     // Smallest denormal
+        0x0000_8000_0000_0000_0000,     // Pseudo-denormal
+        0x3FFF_0000_0000_0000_0001,     // Unnormal
+        0x7FFF_8000_0000_0000_0000,     // Infinity
+        0x7FFF_0000_0000_0000_0000,     // Pseudo-infinity
+        0xFFFF_C000_0000_0000_0000,     // Real indefinite
+        0x7FFF_4000_0000_0000_0000,     // Pseudo-NaN
+    ] {
+        let class = classify(FormatDesc::X87_EXTENDED, storage);
+        let value = parse(FormatDesc::X87_EXTENDED, storage).unwrap();
+        let encoded = encode(FormatDesc::X87_EXTENDED, &value, &mut Context::default());
+        println!("{:#x}: {:?} {:?} ({:#x})", storage, class, value, encoded);
+    }
+}
+
+fn print_fp8() {
+    for x in [1.0f32, 0.3, 448.0, 470.0, 1e6, f32::INFINITY, f32::NAN] {
+        let value = parse(FormatDesc::BINARY32, x.to_bits()).unwrap();
+        for desc in [FormatDesc::E4M3, FormatDesc::E5M2] {
+            for overflow in [OverflowMode::NonSaturating, OverflowMode::Saturating] {
+                let mut ctx = Context {
+                    overflow,
+                    ..Context::default()
+                };
+                let encoded = encode(desc, &value, &mut ctx);
+                println!(
+                    "{:?} -> {:#04x} {:?} {:?}",
+                    x,
+                    encoded,
+                    parse(desc, encoded.clone()).unwrap(),
+                    ctx.flags
+                );
+            }
+        }
+    }
+}
+
+fn print_format_names() {
+    for name in [
+        "binary16", "BF16", "tf32", "fp32", "e4m3", "e5m2", "e8m23", "e11m52", "e3m4", "e0m3",
+        "float", "ibm32", "vax_g",
+    ] {
+        match FormatDesc::from_name(name) {
+            Ok(desc) => println!("{}: {} ({} bits)", name, desc, desc.sign_shift() + 1),
+            Err(err) => println!("{}: {}", name, err),
+        }
+    }
+    println!("{}", parse(FormatDesc::E4M3, 0x100u32).unwrap_err());
+
+    // A printed name never reads back as a different format. The IEEE754-like
+    // E4M3, whose widths are taken by the OCP format, has no name at all.
+    for desc in [
+        FormatDesc::E4M3,
+        FormatDesc::ieee(3, 4),
+        FormatDesc::ieee(4, 3),
+    ] {
+        let name = desc.to_string();
+        let read_back = FormatDesc::from_name(&name).ok().map(|named| named == desc);
+        println!("{}: {:?}", name, read_back);
+    }
+}
+
+fn print_custom_formats() {
+    // A bfloat16 lookalike with a bias of 128 and no subnormals.
+    let desc = FormatDesc::BFLOAT16
+        .with_bias(128)
+        .unwrap()
+        .without_subnormals();
+    for storage in [0x0001u16, 0x0080, 0x3f80, 0x4000] {
+        println!("{:#06x}: {:?}", storage, parse(desc, storage).unwrap());
+    }
+    for x in [1f32, 1e-38, 2e-39, -1e-45] {
+        let mut ctx = Context::default();
+        let encoded = encode(
+            desc,
+            &parse(FormatDesc::BINARY32, x.to_bits()).unwrap(),
+            &mut ctx,
+        );
+        println!("{:?} -> {:#06x} {:?}", x, encoded, ctx.flags);
+    }
+}
+
+fn print_tininess() {
+    // `2^-126 - 2^-151` lies below the smallest normal f32, but rounding it
+    // to 24 bits with an unbounded exponent range lands exactly on `2^-126`.
+    let value = ArbFloat::new(
+        FloatKind::Regular { exp: -151 },
+        BigInt::from((1 << 25) - 1),
+    );
+    for tininess in [TininessMode::BeforeRounding, TininessMode::AfterRounding] {
+        let mut ctx = Context {
+            tininess,
+            ..Context::default()
+        };
+        let encoded = encode(FormatDesc::BINARY32, &value, &mut ctx);
+        println!("{:?}: {:#x} {:?}", tininess, encoded, ctx.flags);
+        // Flushing to zero goes by the same tininess, as ARM's FZ does.
+        let mut ctx = Context {
+            tininess,
+            ..Context::default()
+        };
+        let desc = FormatDesc::BINARY32.without_subnormals();
+        let encoded = encode(desc, &value, &mut ctx);
+        println!("{:?}, flushing: {:#x} {:?}", tininess, encoded, ctx.flags);
+    }
+}
+
+fn print_legacy_formats() {
+    // 1, -118.625, and 1 again but unnormalized.
+    for storage in [0x41100000u32, 0xc276a000, 0x42010000] {
+        let value = parse(FormatDesc::HFP_SHORT, storage).unwrap();
+        println!("hfp_short {:#010x}: {:?}", storage, value);
+    }
+    // IBM hardware truncates.
+    for rounding in [RoundingMode::TiesToEven, RoundingMode::TowardZero] {
+        let mut ctx = Context::new(rounding);
+        let tenth = parse(FormatDesc::BINARY64, 0.1f64.to_bits()).unwrap();
+        let encoded = encode(FormatDesc::HFP_SHORT, &tenth, &mut ctx);
+        println!("0.1 {:?}: {:#010x} {:?}", rounding, encoded, ctx.flags);
+    }
+    // Below the normal range, exact values are encoded unnormalized, and the
+    // rest flush to zero.
+    for storage in [0x000ed1feu32, 0x80000001] {
+        let value = parse(FormatDesc::HFP_SHORT, storage).unwrap();
+        let mut ctx = Context::default();
+        let encoded = encode(FormatDesc::HFP_SHORT, &value, &mut ctx);
+        println!("{:#010x}: {:#010x} {:?}", storage, encoded, ctx.flags);
+    }
+    let mut ctx = Context::default();
+    let tiny = parse(FormatDesc::BINARY64, 1e-80f64.to_bits()).unwrap();
+    let encoded = encode(FormatDesc::HFP_SHORT, &tiny, &mut ctx);
+    println!("1e-80: {:#010x} {:?}", encoded, ctx.flags);
+    // 1 and the reserved operand as little-endian loads, then 1e300 overflowing
+    // into the reserved operand.
+    for storage in [0x00004080u32, 0x00008000] {
+        let value = parse(FormatDesc::VAX_F, storage).unwrap();
+        println!("vax_f {:#010x}: {:?}", storage, value);
+    }
+    let mut ctx = Context::default();
+    let huge = parse(FormatDesc::BINARY64, 1e300f64.to_bits()).unwrap();
+    let encoded = encode(FormatDesc::VAX_F, &huge, &mut ctx);
+    println!("1e300: {:#010x} {:?}", encoded, ctx.flags);
+    let mut ctx = Context::default();
+    let encoded = encode(FormatDesc::VAX_G, &huge, &mut ctx);
+    println!("1e300: {:#018x} {:?}", encoded, ctx.flags);
+}
+
+fn print_posits() {
+    // 1, -1, the largest and smallest posits, and NaR.
+    for storage in [0x40u8, 0xc0, 0x7f, 0x01, 0x80] {
+        let value = parse_posit(PositDesc::POSIT8, storage).unwrap();
+        println!("posit8 {:#04x}: {:?}", storage, value);
+    }
+    // An es of 0 was common in early drafts; the others are unusable.
+    for (nbits, es) in [(8, 0), (1, 2), (8, 40)] {
+        match PositDesc::new(nbits, es) {
+            Ok(desc) => {
+                let largest = parse_posit(desc, 0x7fu8).unwrap();
+                println!("posit{} es{}: 0x7f is {:?}", nbits, es, largest);
+            }
+            Err(err) => println!("posit{} es{}: {}", nbits, es, err),
+        }
+    }
+    for desc in [
+        PositDesc::POSIT8,
+        PositDesc::POSIT16,
+        PositDesc::POSIT32,
+        PositDesc::POSIT64,
+    ] {
+        let tenth = parse(FormatDesc::BINARY64, 0.1f64.to_bits()).unwrap();
+        println!(
+            "0.1 in posit{}: {:#x}",
+            desc.nbits(),
+            encode_posit(desc, &tenth)
+        );
+    }
+    for x in [1e30f64, -1e-30] {
+        let value = parse(FormatDesc::BINARY64, x.to_bits()).unwrap();
+        println!(
+            "{:?} -> {:#06x}",
+            x,
+            encode_posit(PositDesc::POSIT16, &value)
+        );
+    }
+    // With `a = 1 + 2^-11`, `a * a - 1 - 2^-10` is `2^-22`, but rounding the
+    // square to posit16 loses it.
+    let a = ArbFloat::new(FloatKind::Regular { exp: -11 }, BigInt::from((1 << 11) + 1));
+    let one = ArbFloat::new(FloatKind::Regular { exp: 0 }, BigInt::one());
+    let c = ArbFloat::new(FloatKind::Regular { exp: -10 }, BigInt::one());
+    let mut quire = Quire::new(PositDesc::POSIT16);
+    quire.add_product(&a, &a);
+    quire.sub_product(&one, &one);
+    quire.sub_product(&c, &one);
+    let fused = parse_posit(PositDesc::POSIT16, quire.to_posit()).unwrap();
+    println!("fused: {:?}", fused);
+    let square = encode_posit(PositDesc::POSIT16, &(&a * &a));
+    let square = parse_posit(PositDesc::POSIT16, square).unwrap();
+    println!("unfused: {:?}", &(&square - &one) - &c);
+}
+
+fn print_multi_double() {
+    // 1 + 2^-60 is exact as a double-double, and (1, 1) isn't canonical.
+    for components in [[1f64, 2f64.powi(-60)], [1.0, 1.0]] {
+        let value = parse_multi_double(&components.map(f64::to_bits));
+        let mut ctx = Context::default();
+        let canonical = encode_multi_double(&value, 2, &mut ctx);
+        let canonical: Vec<f64> = canonical.into_iter().map(f64::from_bits).collect();
+        println!("{:?}: {:?} -> {:?}", components, value, canonical);
+    }
+    // 1/3 as a double-double and a quad-double.
+    let one = ArbFloat::new(FloatKind::Regular { exp: 0 }, BigInt::one());
+    let three = ArbFloat::new(FloatKind::Regular { exp: 0 }, BigInt::from(3));
+    let mut ctx = Context::default();
+    let third = div_rounded(&one, &three, FormatDesc::BINARY256, &mut ctx);
+    for count in [2, 4] {
+        let mut ctx = Context::default();
+        let components = encode_multi_double(&third, count, &mut ctx);
+        let components: Vec<f64> = components.into_iter().map(f64::from_bits).collect();
+        println!("1/3: {:?} {:?}", components, ctx.flags);
+    }
+}
+
+fn print_mx() {
+    // A scale of 2^-1 with MXFP4 elements 1, 6 and -0.5.
+    let mut elements = [0u8; BLOCK_SIZE];
+    elements[..3].copy_from_slice(&[0x2, 0x7, 0x9]);
+    let block = MxBlock::new(MxFormat::MXFP4, 126, elements);
+    println!("{:?}", &block.decode().unwrap()[..3]);
+    // FP4 elements only take the low four bits of each byte.
+    let block = MxBlock::new(MxFormat::MXFP4, 127, [0x10; BLOCK_SIZE]);
+    println!("{}", block.decode().unwrap_err());
+
+    let values: [ArbFloat; BLOCK_SIZE] = std::array::from_fn(|i| {
+        let x = (i as f32 - 12.0) * 0.37;
+        parse(FormatDesc::BINARY32, x.to_bits()).unwrap()
+    });
+    for (name, format) in [
+        ("mxfp8_e4m3", MxFormat::MXFP8_E4M3),
+        ("mxfp8_e5m2", MxFormat::MXFP8_E5M2),
+        ("mxfp6_e2m3", MxFormat::MXFP6_E2M3),
+        ("mxfp6_e3m2", MxFormat::MXFP6_E3M2),
+        ("mxfp4", MxFormat::MXFP4),
+        ("mxint8", MxFormat::MXINT8),
+    ] {
+        let mut ctx = Context::default();
+        let block = MxBlock::encode(format, &values, &mut ctx);
+        let last = block.decode().unwrap().pop().unwrap();
+        println!("{}: {:?} {:?}", name, last, ctx.flags);
+    }
+
+    // Quiet NaNs carry over into the NaN scale, but signaling NaNs and
+    // infinities signal invalid.
+    for special in [0x7FC0_0000u32, 0x7FA0_0000, 0xFF80_0000] {
+        let mut values = values.clone();
+        values[7] = parse(FormatDesc::BINARY32, special).unwrap();
+        let mut ctx = Context::default();
+        let block = MxBlock::encode(MxFormat::MXFP8_E4M3, &values, &mut ctx);
+        let first = block.decode().unwrap().swap_remove(0);
+        println!("{:#x}: {:?} {:?}", special, first, ctx.flags);
+    }
+}
+
+fn print_fixed() {
+    // Q0.15: -1, the largest value, and the smallest positive one.
+    let q15 = FixedDesc {
+        int_bits: 0,
+        frac_bits: 15,
+        signed: true,
     };
-    for x in 0..8 {
-        println!("{:?}", parse(BINARY3, x));
+    for storage in [0x8000u16, 0x7fff, 0x0001] {
+        println!(
+            "q15 {:#06x}: {:?}",
+            storage,
+            parse_fixed(q15, storage).unwrap()
+        );
+    }
+    // An unsigned Q8.8, with 1.0 out of range for Q0.15.
+    let uq8_8 = FixedDesc {
+        int_bits: 8,
+        frac_bits: 8,
+        signed: false,
+    };
+    for x in [0.3f64, -0.3, 1.0, 300.0] {
+        let value = parse(FormatDesc::BINARY64, x.to_bits()).unwrap();
+        for overflow in [OverflowMode::NonSaturating, OverflowMode::Saturating] {
+            for desc in [q15, uq8_8] {
+                let mut ctx = Context {
+                    overflow,
+                    ..Context::default()
+                };
+                let encoded = encode_fixed(desc, &value, &mut ctx);
+                println!("{:?} {:?}: {:#06x} {:?}", x, overflow, encoded, ctx.flags);
+            }
+        }
+    }
+}
+
+fn print_integers() {
+    let values = [2.5f64, -2.5, -0.5, 1e10, -1e10, f64::NAN, f64::NEG_INFINITY];
+    for x in values {
+        let value = parse(FormatDesc::BINARY64, x.to_bits()).unwrap();
+        for rounding in [RoundingMode::TiesToEven, RoundingMode::TowardZero] {
+            let mut ctx = Context::new(rounding);
+            let int = convert_to_integer(&value, 32, true, &mut ctx);
+            println!("{:?} {:?}: {} {:?}", x, rounding, int, ctx.flags);
+        }
+    }
+    for integer_overflow in [
+        IntegerOverflowMode::RustAs,
+        IntegerOverflowMode::X86,
+        IntegerOverflowMode::Arm,
+        IntegerOverflowMode::RiscV,
+    ] {
+        let ctx = Context {
+            integer_overflow,
+            ..Context::default()
+        };
+        let results: Vec<_> = values[3..]
+            .iter()
+            .map(|x| {
+                let value = parse(FormatDesc::BINARY64, x.to_bits()).unwrap();
+                let signed = convert_to_integer(&value, 32, true, &mut ctx.clone());
+                let unsigned = convert_to_integer(&value, 32, false, &mut ctx.clone());
+                (signed, unsigned)
+            })
+            .collect();
+        println!("{:?}: {:?}", integer_overflow, results);
+    }
+
+    // Zero-width integers only hold 0, and values far out of range saturate
+    // without being turned into integers first.
+    let half = ArbFloat::new(FloatKind::Regular { exp: -1 }, BigInt::one());
+    let huge = ArbFloat::new(FloatKind::Regular { exp: 1_500_000_000 }, BigInt::one());
+    for (value, width) in [(&half, 0), (&huge, 0), (&huge, 64)] {
+        let mut ctx = Context::default();
+        let int = convert_to_integer(value, width, true, &mut ctx);
+        println!("i{}: {} {:?}", width, int, ctx.flags);
+    }
+}
+
+fn print_from_integers() {
+    let cases = [
+        (BigInt::from(u64::MAX), FormatDesc::BINARY32),
+        (BigInt::from((1 << 24) + 1), FormatDesc::BINARY32),
+        (BigInt::from(-70000), FormatDesc::BINARY16),
+        (BigInt::from(1000), FormatDesc::E4M3),
+        (BigInt::from(1000), FormatDesc::E5M2),
+        (BigInt::one() << 200u32, FormatDesc::BINARY64),
+    ];
+    for (int, desc) in cases {
+        let mut ctx = Context::default();
+        let value = convert_from_int(int.clone(), desc, &mut ctx);
+        println!("{} -> {}: {:?} {:?}", int, desc, value, ctx.flags);
+    }
+}
+
+fn print_nan_payloads() {
+    // Widening quiet NaNs keeps their payload at the top of the fraction, as
+    // the hardware does. Operations quiet signaling NaNs, and signal invalid.
+    for x in [0x7FC0_1234u32, 0xFFC0_0000, 0x7FA0_0001] {
+        let value = parse(FormatDesc::BINARY32, x).unwrap();
+        let widened = encode(FormatDesc::BINARY64, &value, &mut Context::default());
+        let mut ctx = Context::default();
+        let two = parse(FormatDesc::BINARY32, 2f32.to_bits()).unwrap();
+        let product = value.mul_exact(&two, &mut ctx);
+        let product = encode(FormatDesc::BINARY32, &product, &mut ctx);
+        println!(
+            "{:#x}: {:?} -> {:#x}, * 2 = {:#x} {:?} (vs. {:#x})",
+            x,
+            value,
+            widened,
+            product,
+            ctx.flags,
+            (f32::from_bits(x) * 2.0).to_bits()
+        );
+    }
+
+    // Narrowing drops the bottom of the payload.
+    let value = parse(FormatDesc::BINARY64, 0x7FF8_0000_2000_0001u64).unwrap();
+    let narrowed = encode(FormatDesc::BINARY32, &value, &mut Context::default());
+    println!("{:?} -> {:#x}", value, narrowed);
+
+    // Legacy MIPS reads the quiet bit the other way around, and its default
+    // NaN has every payload bit set instead.
+    let legacy = FormatDesc::BINARY32.with_legacy_nans();
+    for x in [0x7FC0_0000u32, 0x7FBF_FFFF, 0x7F80_1234] {
+        let value = parse(legacy, x).unwrap();
+        let converted = encode(FormatDesc::BINARY32, &value, &mut Context::default());
+        println!("{:#x}: {:?} -> {:#x}", x, value, converted);
+    }
+    let nan = ArbFloat::new(FloatKind::QUIET_NAN, BigInt::one());
+    println!("{:#x}", encode(legacy, &nan, &mut Context::default()));
+}
+
+fn print_nan_policies() {
+    let quiet = 0x7FC0_1234u32;
+    let signaling = 0xFFA0_0042u32;
+    let [quiet_value, signaling_value, zero, inf] =
+        [quiet, signaling, 0, 0x7F80_0000].map(|x| parse(FormatDesc::BINARY32, x).unwrap());
+    for nan in [
+        NanPolicy::Ieee,
+        NanPolicy::X86,
+        NanPolicy::Arm,
+        NanPolicy::ArmDefaultNan,
+        NanPolicy::RiscV,
+    ] {
+        let mut ctx = Context {
+            nan,
+            ..Context::default()
+        };
+        let sum = quiet_value.add_exact(&signaling_value, &mut ctx);
+        let difference = zero.sub_exact(&quiet_value, &mut ctx);
+        let product = zero.mul_exact(&inf, &mut ctx);
+        let fused = fma(&zero, &inf, &quiet_value, FormatDesc::BINARY32, &mut ctx);
+        let widened = convert_format(&signaling_value, FormatDesc::BINARY64, &mut ctx);
+        println!(
+            "{:?}: {:#x} {:#x} {:#x} {:#x} {:#x} {:?}",
+            nan,
+            encode(FormatDesc::BINARY32, &sum, &mut ctx),
+            encode(FormatDesc::BINARY32, &difference, &mut ctx),
+            encode(FormatDesc::BINARY32, &product, &mut ctx),
+            encode(FormatDesc::BINARY32, &fused, &mut ctx),
+            encode(FormatDesc::BINARY64, &widened, &mut ctx),
+            ctx.flags
+        );
+    }
+    // Keep the compiler from folding the operations, as it doesn't follow
+    // the hardware.
+    let [quiet, signaling, zero, inf] =
+        [quiet, signaling, 0, 0x7F80_0000].map(|x| black_box(f32::from_bits(x)));
+    println!(
+        "x86 hardware: {:#x} {:#x} {:#x} {:#x}",
+        (quiet + signaling).to_bits(),
+        (zero - quiet).to_bits(),
+        (zero * inf).to_bits(),
+        (signaling as f64).to_bits()
+    );
+}
+
+fn print_decimal() {
+    // 1 in decimal64, as BID and as DPD.
+    for (encoding, storage) in [
+        (DecimalEncoding::Bid, 0x31c0000000000001u64),
+        (DecimalEncoding::Dpd, 0x2238000000000001),
+    ] {
+        let value = parse_decimal(DecimalDesc::DECIMAL64, encoding, storage).unwrap();
+        println!("{:?} {:#018x}: {:?}", encoding, storage, value);
     }
+    // 1, 1.0 and 1.00 are equal but encode differently.
+    for (coeff, exp) in [(1u32, 0), (10, -1), (100, -2)] {
+        let value = DecimalFloat::new(DecimalKind::Finite { exp }, false, coeff);
+        let mut ctx = Context::default();
+        let bid = encode_decimal(
+            DecimalDesc::DECIMAL32,
+            DecimalEncoding::Bid,
+            &value,
+            &mut ctx,
+        );
+        let dpd = encode_decimal(
+            DecimalDesc::DECIMAL32,
+            DecimalEncoding::Dpd,
+            &value,
+            &mut ctx,
+        );
+        println!("{}e{}: BID {:#010x} DPD {:#010x}", coeff, exp, bid, dpd);
+    }
+    // A BID coefficient above 9999999 and a non-canonical declet for 888.
+    let bid = parse_decimal(DecimalDesc::DECIMAL32, DecimalEncoding::Bid, 0x6cbfffffu32);
+    println!("{:?}", bid.unwrap());
+    let dpd = parse_decimal(DecimalDesc::DECIMAL32, DecimalEncoding::Dpd, 0x2250036eu32);
+    println!("{:?}", dpd.unwrap());
+    let value = DecimalFloat::new(DecimalKind::Finite { exp: -10 }, false, 12345678901u64);
+    let mut ctx = Context::default();
+    let encoded = encode_decimal(
+        DecimalDesc::DECIMAL32,
+        DecimalEncoding::Dpd,
+        &value,
+        &mut ctx,
+    );
+    println!("{:?} -> {:#010x} {:?}", value, encoded, ctx.flags);
+    // 10^6144 needs an exponent above the maximum, so it gets padded zeros.
+    let value = DecimalFloat::new(DecimalKind::Finite { exp: 6144 }, false, 1u32);
+    let mut ctx = Context::default();
+    let encoded = encode_decimal(
+        DecimalDesc::DECIMAL128,
+        DecimalEncoding::Bid,
+        &value,
+        &mut ctx,
+    );
+    println!("{:?} -> {:#034x} {:?}", value, encoded, ctx.flags);
+    // Far below the smallest subnormal, rounding up gives the smallest one.
+    let value = DecimalFloat::new(
+        DecimalKind::Finite {
+            exp: -1_000_000_000,
+        },
+        false,
+        1u32,
+    );
+    let mut ctx = Context::new(RoundingMode::TowardPositive);
+    let encoded = encode_decimal(
+        DecimalDesc::DECIMAL32,
+        DecimalEncoding::Bid,
+        &value,
+        &mut ctx,
+    );
+    println!("{:?} -> {:#010x} {:?}", value, encoded, ctx.flags);
 }
 
 fn main() {
     print_examples();
-    println!("");
+    println!();
     print_binary3();
+    println!();
+    print_rounding();
+    println!();
+    print_multiplication();
+    println!();
+    print_addition();
+    println!();
+    print_division();
+    println!();
+    print_sqrt();
+    println!();
+    print_fma();
+    println!();
+    print_tininess();
+    println!();
+    print_wide_formats();
+    println!();
+    print_x87();
+    println!();
+    print_fp8();
+    println!();
+    print_format_names();
+    println!();
+    print_custom_formats();
+    println!();
+    print_decimal();
+    println!();
+    print_legacy_formats();
+    println!();
+    print_posits();
+    println!();
+    print_multi_double();
+    println!();
+    print_mx();
+    println!();
+    print_fixed();
+    println!();
+    print_integers();
+    println!();
+    print_from_integers();
+    println!();
+    print_nan_payloads();
+    println!();
+    print_nan_policies();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NONE: StatusFlags = StatusFlags(0);
+    const INEXACT: StatusFlags = StatusFlags::INEXACT;
+    const TINY: StatusFlags = StatusFlags(StatusFlags::UNDERFLOW.0 | StatusFlags::INEXACT.0);
+
+    fn binary32(bits: u32) -> ArbFloat {
+        parse(FormatDesc::BINARY32, bits).unwrap()
+    }
+
+    fn f32_value(x: f32) -> ArbFloat {
+        binary32(x.to_bits())
+    }
+
+    // Rounds `value` to binary32, and returns the encoding with the flags
+    // raised along the way.
+    fn encode_binary32(value: &ArbFloat, ctx: &mut Context) -> (IntStorage, StatusFlags) {
+        let encoded = encode(FormatDesc::BINARY32, value, ctx);
+        (encoded, ctx.flags)
+    }
+
+    #[test]
+    fn multiplication_matches_hardware() {
+        for (x, y, flags) in [
+            (1.1f32, 3.3f32, INEXACT),
+            (-0.0, 5.0, NONE),
+            (f32::MAX, 2.0, StatusFlags::OVERFLOW | INEXACT),
+            (1e-30, 1e-10, TINY),
+        ] {
+            let product = f32_value(x) * f32_value(y);
+            let result = encode_binary32(&product, &mut Context::default());
+            assert_eq!(result, ((x * y).to_bits().into(), flags), "{x:?} * {y:?}");
+        }
+    }
+
+    #[test]
+    fn addition_matches_hardware() {
+        for (x, y, flags) in [
+            (0.1f32, 0.2f32, INEXACT),
+            (1.0, -1.0, NONE),
+            (-0.0, -0.0, NONE),
+            (1e30, 1e-30, INEXACT),
+        ] {
+            let sum = f32_value(x) + f32_value(y);
+            let result = encode_binary32(&sum, &mut Context::default());
+            assert_eq!(result, ((x + y).to_bits().into(), flags), "{x:?} + {y:?}");
+        }
+    }
+
+    #[test]
+    fn exact_cancellation_is_negative_only_toward_negative() {
+        let one = f32_value(1.0);
+        for mode in RoundingMode::ALL {
+            let mut ctx = Context::new(mode);
+            let difference = one.sub_exact(&one, &mut ctx);
+            let expected = match mode {
+                RoundingMode::TowardNegative => 0x8000_0000u32,
+                _ => 0,
+            };
+            let result = encode_binary32(&difference, &mut ctx);
+            assert_eq!(result, (expected.into(), NONE), "{mode:?}");
+        }
+    }
+
+    #[test]
+    fn division_matches_hardware() {
+        for (x, y, flags) in [
+            (1f32, 3f32, INEXACT),
+            (-1.0, 0.0, StatusFlags::DIVIDE_BY_ZERO),
+            (0.0, -2.0, NONE),
+            (1e-38, 3e3, TINY),
+        ] {
+            let mut ctx = Context::default();
+            let desc = FormatDesc::BINARY32;
+            let quot = div_rounded(&f32_value(x), &f32_value(y), desc, &mut ctx);
+            let result = encode_binary32(&quot, &mut ctx);
+            assert_eq!(result, ((x / y).to_bits().into(), flags), "{x:?} / {y:?}");
+        }
+    }
+
+    #[test]
+    fn division_by_zero_without_infinities() {
+        // Only divide by zero, not the invalid of converting an infinity.
+        let one = ArbFloat::from_integer(BigInt::one());
+        let zero = ArbFloat::from_integer(BigInt::zero());
+        for (desc, expected) in [(FormatDesc::E4M3, 0x7fu32), (FormatDesc::FP4_E2M1, 0x7)] {
+            let mut ctx = Context::default();
+            let quot = div_rounded(&one, &zero, desc, &mut ctx);
+            let encoded = encode(desc, &quot, &mut ctx);
+            assert_eq!(encoded, expected.into(), "{desc}");
+            assert_eq!(ctx.flags, StatusFlags::DIVIDE_BY_ZERO, "{desc}");
+        }
+    }
+
+    #[test]
+    fn sqrt_matches_hardware() {
+        for (x, flags) in [
+            (2f32, INEXACT),
+            (0.25, NONE),
+            (-0.0, NONE),
+            (9.0, NONE),
+            (1e-45, INEXACT),
+        ] {
+            let mut ctx = Context::default();
+            let root = sqrt_rounded(&f32_value(x), FormatDesc::BINARY32, &mut ctx);
+            let result = encode_binary32(&root, &mut ctx);
+            assert_eq!(result, (x.sqrt().to_bits().into(), flags), "sqrt({x:?})");
+        }
+    }
+
+    #[test]
+    fn fma_matches_hardware() {
+        for (x, y, z) in [
+            (0.1f32, 10f32, -1f32),
+            (1.0, -0.0, 0.0),
+            (3.0, 1e-40, 1e-45),
+        ] {
+            let mut ctx = Context::default();
+            let result = fma(
+                &f32_value(x),
+                &f32_value(y),
+                &f32_value(z),
+                FormatDesc::BINARY32,
+                &mut ctx,
+            );
+            // All three are exact, even the subnormal one.
+            let result = encode_binary32(&result, &mut ctx);
+            let expected = x.mul_add(y, z).to_bits().into();
+            assert_eq!(result, (expected, NONE), "fma({x:?}, {y:?}, {z:?})");
+        }
+    }
+
+    #[test]
+    fn tininess_decides_flushing() {
+        // `2^-126 - 2^-151` is tiny before rounding, but not after.
+        let value = ArbFloat::new(
+            FloatKind::Regular { exp: -151 },
+            BigInt::from((1 << 25) - 1),
+        );
+        let flushing = FormatDesc::BINARY32.without_subnormals();
+        for (tininess, desc, expected) in [
+            (
+                TininessMode::BeforeRounding,
+                FormatDesc::BINARY32,
+                (0x800000u32, TINY),
+            ),
+            (TininessMode::BeforeRounding, flushing, (0, TINY)),
+            (
+                TininessMode::AfterRounding,
+                FormatDesc::BINARY32,
+                (0x800000, INEXACT),
+            ),
+            (TininessMode::AfterRounding, flushing, (0x800000, INEXACT)),
+        ] {
+            let mut ctx = Context {
+                tininess,
+                ..Context::default()
+            };
+            let encoded = encode(desc, &value, &mut ctx);
+            assert_eq!(
+                (encoded, ctx.flags),
+                (expected.0.into(), expected.1),
+                "{tininess:?} {desc}"
+            );
+        }
+    }
+
+    #[test]
+    fn nan_payloads_survive_operations() {
+        // Widened, and multiplied by 2 as the hardware does.
+        for (x, widened, product, flags) in [
+            (
+                0x7FC0_1234u32,
+                0x7FF8_0246_8000_0000u64,
+                0x7FC0_1234u32,
+                NONE,
+            ),
+            (0xFFC0_0000, 0xFFF8_0000_0000_0000, 0xFFC0_0000, NONE),
+            (
+                0x7FA0_0001,
+                0x7FF4_0000_2000_0000,
+                0x7FE0_0001,
+                StatusFlags::INVALID,
+            ),
+        ] {
+            let value = binary32(x);
+            let encoded = encode(FormatDesc::BINARY64, &value, &mut Context::default());
+            assert_eq!(encoded, widened.into(), "{x:#x}");
+            let mut ctx = Context::default();
+            let doubled = value.mul_exact(&f32_value(2.0), &mut ctx);
+            let result = encode_binary32(&doubled, &mut ctx);
+            assert_eq!(result, (product.into(), flags), "{x:#x} * 2");
+        }
+        let value = parse(FormatDesc::BINARY64, 0x7FF8_0000_2000_0001u64).unwrap();
+        let narrowed = encode(FormatDesc::BINARY32, &value, &mut Context::default());
+        assert_eq!(narrowed, 0x7FC0_0001u32.into());
+    }
+
+    // `quiet + signaling`, `0 - quiet`, `0 * inf`, `fma(0, inf, quiet)` and
+    // `signaling` widened to binary64, under `nan`.
+    fn nan_policy_results(nan: NanPolicy) -> ([IntStorage; 5], StatusFlags) {
+        let [quiet, signaling, zero, inf] =
+            [0x7FC0_1234u32, 0xFFA0_0042, 0, 0x7F80_0000].map(binary32);
+        let mut ctx = Context {
+            nan,
+            ..Context::default()
+        };
+        let sum = quiet.add_exact(&signaling, &mut ctx);
+        let difference = zero.sub_exact(&quiet, &mut ctx);
+        let product = zero.mul_exact(&inf, &mut ctx);
+        let fused = fma(&zero, &inf, &quiet, FormatDesc::BINARY32, &mut ctx);
+        let widened = convert_format(&signaling, FormatDesc::BINARY64, &mut ctx);
+        let results = [
+            encode(FormatDesc::BINARY32, &sum, &mut ctx),
+            encode(FormatDesc::BINARY32, &difference, &mut ctx),
+            encode(FormatDesc::BINARY32, &product, &mut ctx),
+            encode(FormatDesc::BINARY32, &fused, &mut ctx),
+            encode(FormatDesc::BINARY64, &widened, &mut ctx),
+        ];
+        (results, ctx.flags)
+    }
+
+    #[test]
+    fn nan_policies() {
+        for (nan, expected) in [
+            (
+                NanPolicy::Ieee,
+                [0x7fc01234u64, 0x7fc01234, 0x7fc00000, 0x7fc01234],
+            ),
+            (
+                NanPolicy::X86,
+                [0x7fc01234, 0x7fc01234, 0xffc00000, 0x7fc01234],
+            ),
+            (
+                NanPolicy::Arm,
+                [0xffe00042, 0x7fc01234, 0x7fc00000, 0x7fc00000],
+            ),
+            (NanPolicy::ArmDefaultNan, [0x7fc00000; 4]),
+            (NanPolicy::RiscV, [0x7fc00000; 4]),
+        ] {
+            let widened: u64 = match nan {
+                NanPolicy::ArmDefaultNan | NanPolicy::RiscV => 0x7ff8000000000000,
+                _ => 0xfffc000840000000,
+            };
+            let [a, b, c, d] = expected.map(IntStorage::from);
+            let expected = ([a, b, c, d, widened.into()], StatusFlags::INVALID);
+            assert_eq!(nan_policy_results(nan), expected, "{nan:?}");
+        }
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    #[test]
+    fn x86_nan_policy_matches_hardware() {
+        // Keep the compiler from folding the operations, as it doesn't follow
+        // the hardware.
+        let [quiet, signaling, zero, inf] =
+            [0x7FC0_1234u32, 0xFFA0_0042, 0, 0x7F80_0000].map(|x| black_box(f32::from_bits(x)));
+        let hardware = [
+            (quiet + signaling).to_bits().into(),
+            (zero - quiet).to_bits().into(),
+            (zero * inf).to_bits().into(),
+            zero.mul_add(inf, quiet).to_bits().into(),
+            (signaling as f64).to_bits().into(),
+        ];
+        assert_eq!(nan_policy_results(NanPolicy::X86).0, hardware);
+    }
+
+    #[test]
+    fn integer_conversions_out_of_range() {
+        let half = ArbFloat::new(FloatKind::Regular { exp: -1 }, BigInt::one());
+        let huge = ArbFloat::new(FloatKind::Regular { exp: 1_500_000_000 }, BigInt::one());
+        for (value, width, expected, flags) in [
+            (&half, 0, BigInt::zero(), INEXACT),
+            (&huge, 0, BigInt::zero(), StatusFlags::INVALID),
+            (&huge, 64, BigInt::from(i64::MAX), StatusFlags::INVALID),
+        ] {
+            let mut ctx = Context::default();
+            let int = convert_to_integer(value, width, true, &mut ctx);
+            assert_eq!((int, ctx.flags), (expected, flags), "i{width}");
+        }
+    }
+
+    #[test]
+    fn decimal_far_below_the_smallest_subnormal() {
+        let value = DecimalFloat::new(
+            DecimalKind::Finite {
+                exp: -1_000_000_000,
+            },
+            false,
+            1u32,
+        );
+        for (rounding, expected) in [
+            (RoundingMode::TiesToEven, 0u32),
+            (RoundingMode::TowardPositive, 1),
+        ] {
+            let mut ctx = Context::new(rounding);
+            let encoded = encode_decimal(
+                DecimalDesc::DECIMAL32,
+                DecimalEncoding::Bid,
+                &value,
+                &mut ctx,
+            );
+            assert_eq!(
+                (encoded, ctx.flags),
+                (expected.into(), TINY),
+                "{rounding:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn posit_formats_are_checked() {
+        assert!(PositDesc::new(8, 0).is_ok());
+        assert_eq!(PositDesc::new(1, 2), Err(FormatError::PositTooNarrow(1)));
+        assert_eq!(
+            PositDesc::new(8, 40),
+            Err(FormatError::PositScaleOutOfRange { nbits: 8, es: 40 })
+        );
+    }
 }
@@ -0,0 +1,72 @@
+use crate::{encode, parse, ArbFloat, Context, FloatKind, FormatDesc, RoundingMode, StatusFlags};
+use num_bigint::BigInt;
+use num_traits::Signed;
+
+// Double-double and quad-double arithmetic represent a value as the
+// unevaluated sum of two or four binary64 components. We read such a tuple,
+// given as the components' encodings in any order, by adding them up exactly.
+pub fn parse_multi_double(components: &[u64]) -> ArbFloat {
+    // The only thing that could go wrong is adding infinities of opposite
+    // signs, which already yields a NaN.
+    let mut ctx = Context::default();
+    let mut components = components
+        .iter()
+        .map(|&bits| parse(FormatDesc::BINARY64, bits).unwrap());
+    // Starting from the first component rather than from +0 keeps the sign
+    // of a sum of negative zeros.
+    let first = components
+        .next()
+        .unwrap_or_else(|| ArbFloat::new(FloatKind::Zero, BigInt::from(1)));
+    components.fold(first, |sum, component| sum.add_exact(&component, &mut ctx))
+}
+
+// Splits `value` into `count` binary64 components, from the most significant
+// down, in the canonical form that renormalization produces: each component
+// is the remainder rounded to nearest, so it's within half an ulp of the
+// previous one, and the components don't overlap. Only the last component is
+// rounded as `ctx` directs, and the flags it raises are those of the whole
+// conversion. Once the value is exhausted, the rest are zeros of its sign.
+pub fn encode_multi_double(value: &ArbFloat, count: usize, ctx: &mut Context) -> Vec<u64> {
+    // The last remainder may not have the sign of the value, so rounding it
+    // toward zero could round the value away from zero.
+    let rounding = match ctx.rounding {
+        RoundingMode::TowardZero if value.num.is_negative() => RoundingMode::TowardPositive,
+        RoundingMode::TowardZero => RoundingMode::TowardNegative,
+        rounding => rounding,
+    };
+    let mut components = Vec::with_capacity(count);
+    let mut remainder = value.clone();
+    while components.len() < count {
+        let mut nearest = Context {
+            rounding: RoundingMode::TiesToEven,
+            flags: StatusFlags::default(),
+            ..ctx.clone()
+        };
+        let mut component = FormatDesc::BINARY64.round(&remainder, &mut nearest);
+        // There's no representing a remainder below an overflowed component,
+        // so that's where the conversion ends.
+        let overflow = nearest.flags.contains(StatusFlags::OVERFLOW);
+        if overflow || components.len() + 1 == count {
+            let mut last = Context {
+                rounding,
+                ..ctx.clone()
+            };
+            component = FormatDesc::BINARY64.round(&remainder, &mut last);
+            ctx.raise(last.flags);
+        }
+        components.push(component.clone());
+        if overflow || !matches!(remainder.kind, FloatKind::Regular { .. }) {
+            break;
+        }
+        remainder = remainder.sub_exact(&component, ctx);
+        if remainder.kind == FloatKind::Zero {
+            break;
+        }
+    }
+    let zero = ArbFloat::new(FloatKind::Zero, value.num.signum());
+    components.resize(count, zero);
+    components
+        .iter()
+        .map(|component| u64::try_from(encode(FormatDesc::BINARY64, component, ctx)).unwrap())
+        .collect()
+}
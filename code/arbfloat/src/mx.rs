@@ -0,0 +1,174 @@
+use crate::{
+    encode, parse, ArbFloat, Context, FloatKind, FormatDesc, FormatError, OverflowMode, StatusFlags,
+};
+use num_bigint::BigInt;
+use num_traits::{Signed, ToPrimitive};
+
+// The number of elements sharing a scale in every OCP MX format.
+pub const BLOCK_SIZE: usize = 32;
+
+// The scale is an E8M0 number: a bare biased exponent, without sign or
+// significand, worth `2^(scale - 127)`. All ones is the NaN.
+const SCALE_BIAS: i32 = 127;
+const SCALE_NAN: u8 = 0xff;
+
+// The element type of an OCP Microscaling (MX) format.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MxFormat {
+    Float(FormatDesc),
+    // Two's complement integers scaled by `2^-6`, from -2 to `1 + 63/64`.
+    Int8,
+}
+
+impl MxFormat {
+    pub const MXFP8_E4M3: Self = Self::Float(FormatDesc::E4M3);
+    pub const MXFP8_E5M2: Self = Self::Float(FormatDesc::E5M2);
+    pub const MXFP6_E2M3: Self = Self::Float(FormatDesc::FP6_E2M3);
+    pub const MXFP6_E3M2: Self = Self::Float(FormatDesc::FP6_E3M2);
+    pub const MXFP4: Self = Self::Float(FormatDesc::FP4_E2M1);
+    pub const MXINT8: Self = Self::Int8;
+
+    const INT8_EXP: i32 = -6;
+
+    // The exponent of the top binade of the elements.
+    fn emax(&self) -> i32 {
+        match self {
+            Self::Float(desc) => desc.emax(),
+            Self::Int8 => 0,
+        }
+    }
+
+    fn parse_element(&self, bits: u8) -> Result<ArbFloat, FormatError> {
+        match self {
+            Self::Float(desc) => parse(*desc, bits),
+            Self::Int8 => {
+                let int = bits as i8;
+                if int == 0 {
+                    Ok(ArbFloat::new(FloatKind::Zero, BigInt::from(1)))
+                } else {
+                    let exp = Self::INT8_EXP;
+                    Ok(ArbFloat::new(FloatKind::Regular { exp }, BigInt::from(int)))
+                }
+            }
+        }
+    }
+
+    // Rounds an element that has already been divided by the block's scale.
+    // Per the OCP spec, elements that overflow saturate.
+    fn encode_element(&self, value: &ArbFloat, ctx: &mut Context) -> u8 {
+        let mut saturating = Context {
+            overflow: OverflowMode::Saturating,
+            flags: StatusFlags::default(),
+            ..ctx.clone()
+        };
+        let bits = match self {
+            Self::Float(desc) => encode(*desc, value, &mut saturating).to_u8().unwrap(),
+            Self::Int8 => {
+                let FloatKind::Regular { exp } = value.kind else {
+                    // Infinities and NaNs never make it into an element: they
+                    // set the scale to NaN instead.
+                    return 0;
+                };
+                let negative = value.num.is_negative();
+                let num = value.num.magnitude();
+                let mode = saturating.rounding;
+                let mut int = ArbFloat::round_mantissa(num, exp, Self::INT8_EXP, negative, mode)
+                    .to_i32()
+                    .unwrap_or(i32::MAX);
+                // `num` is odd, so any bits below the quantum make it inexact.
+                if exp < Self::INT8_EXP {
+                    saturating.raise(StatusFlags::INEXACT);
+                }
+                let max = if negative { 128 } else { 127 };
+                if int > max {
+                    saturating.raise(StatusFlags::OVERFLOW | StatusFlags::INEXACT);
+                    int = max;
+                }
+                (if negative { -int } else { int }) as u8
+            }
+        };
+        ctx.raise(saturating.flags);
+        bits
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MxBlock {
+    format: MxFormat,
+    scale: u8,
+    elements: [u8; BLOCK_SIZE],
+}
+
+impl MxBlock {
+    pub fn new(format: MxFormat, scale: u8, elements: [u8; BLOCK_SIZE]) -> Self {
+        Self {
+            format,
+            scale,
+            elements,
+        }
+    }
+
+    // Elements of narrow formats are held in the low bits of each byte, and
+    // any bits set above them are an error, as in `parse`.
+    pub fn decode(&self) -> Result<Vec<ArbFloat>, FormatError> {
+        let elements = self
+            .elements
+            .iter()
+            .map(|&bits| self.format.parse_element(bits))
+            .collect::<Result<Vec<_>, _>>()?;
+        let nan = ArbFloat::new(FloatKind::QUIET_NAN, BigInt::from(1));
+        if self.scale == SCALE_NAN {
+            return Ok(vec![nan; BLOCK_SIZE]);
+        }
+        let exp = self.scale as i32 - SCALE_BIAS;
+        let scale = ArbFloat::new(FloatKind::Regular { exp }, BigInt::from(1));
+        // Scaling by a power of two is exact, and there's nothing invalid
+        // about it, e.g. no zero times infinity.
+        Ok(elements
+            .iter()
+            .map(|element| element.mul_exact(&scale, &mut Context::default()))
+            .collect())
+    }
+
+    // Picks the shared scale as the OCP spec does: the largest magnitude's
+    // exponent, less the exponent of the elements' top binade, so that the
+    // largest element lands in it. Each element is then divided by the scale
+    // and rounded as `ctx` directs, saturating on overflow. A block with an
+    // infinity or a NaN gets a NaN scale. Like any operation, this signals
+    // invalid for signaling NaNs, but not for quiet ones, which just carry
+    // over. MX formats have no infinities, so turning one into a NaN signals
+    // invalid too, as converting it to E4M3 does.
+    pub fn encode(format: MxFormat, values: &[ArbFloat; BLOCK_SIZE], ctx: &mut Context) -> Self {
+        let signaling = FloatKind::NaN { signaling: true };
+        if values
+            .iter()
+            .any(|value| value.kind == signaling || value.kind == FloatKind::Infinity)
+        {
+            ctx.raise(StatusFlags::INVALID);
+        }
+        if values
+            .iter()
+            .any(|value| matches!(value.kind, FloatKind::NaN { .. } | FloatKind::Infinity))
+        {
+            return Self::new(format, SCALE_NAN, [0; BLOCK_SIZE]);
+        }
+        let max_exp = values
+            .iter()
+            .filter_map(|value| match value.kind {
+                FloatKind::Regular { exp } => Some(exp + value.num.bits() as i32 - 1),
+                _ => None,
+            })
+            .max();
+        // An all-zero block gets the smallest scale.
+        let shared_exp = max_exp.map_or(-SCALE_BIAS, |max_exp| {
+            (max_exp - format.emax()).clamp(-SCALE_BIAS, SCALE_BIAS)
+        });
+        let inverse_scale = ArbFloat::new(FloatKind::Regular { exp: -shared_exp }, BigInt::from(1));
+        let mut elements = [0; BLOCK_SIZE];
+        for (element, value) in elements.iter_mut().zip(values) {
+            let scaled = value.mul_exact(&inverse_scale, &mut Context::default());
+            *element = format.encode_element(&scaled, ctx);
+        }
+        Self::new(format, (shared_exp + SCALE_BIAS) as u8, elements)
+    }
+}
@@ -0,0 +1,209 @@
+use crate::{ArbFloat, Context, FloatKind, FormatDesc, FormatError, IntStorage};
+use num_bigint::BigInt;
+use num_traits::{One, Signed, ToPrimitive, Zero};
+
+// A posit format with `nbits` bits in total and up to `es` exponent bits. The
+// 2022 posit standard fixes `es` at 2, but earlier drafts didn't.
+//
+// After the sign, a posit holds a run-length encoded regime `k`, then the
+// exponent `e` and the fraction, which together shrink as the regime grows.
+// The value is `2^(k * 2^es + e) * 1.frac`. Negative posits are the two's
+// complement of their absolute value.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct PositDesc {
+    nbits: u32,
+    es: u32,
+}
+
+impl PositDesc {
+    pub const POSIT8: Self = Self { nbits: 8, es: 2 };
+    pub const POSIT16: Self = Self { nbits: 16, es: 2 };
+    pub const POSIT32: Self = Self { nbits: 32, es: 2 };
+    pub const POSIT64: Self = Self { nbits: 64, es: 2 };
+
+    // A posit format, checked for usability.
+    pub fn new(nbits: u32, es: u32) -> Result<Self, FormatError> {
+        if nbits < 2 {
+            // There would be no room for the regime, let alone NaR.
+            return Err(FormatError::PositTooNarrow(nbits));
+        }
+        // Scales reach `nbits << es` in magnitude; keep them within the
+        // exponent range of `FormatDesc` for the same reasons.
+        if es >= u32::BITS || (nbits as u64) << es >= 1 << FormatDesc::MAX_EXP_BITS {
+            return Err(FormatError::PositScaleOutOfRange { nbits, es });
+        }
+        Ok(Self { nbits, es })
+    }
+
+    pub fn nbits(&self) -> u32 {
+        self.nbits
+    }
+
+    // Not-a-Real, the single exceptional value: the sign bit alone.
+    fn nar(&self) -> IntStorage {
+        IntStorage::one() << (self.nbits - 1)
+    }
+
+    // The largest posit, all ones but the sign, is `2^max_scale`, and the
+    // smallest positive one is `2^-max_scale`.
+    fn max_scale(&self) -> i32 {
+        (self.nbits as i32 - 2) << self.es
+    }
+
+    // Two's complement negation within `nbits`.
+    fn negate(&self, storage: IntStorage) -> IntStorage {
+        ((IntStorage::one() << self.nbits) - storage) & FormatDesc::mask(self.nbits)
+    }
+}
+
+pub fn parse_posit(
+    desc: PositDesc,
+    storage: impl Into<IntStorage>,
+) -> Result<ArbFloat, FormatError> {
+    let storage = storage.into();
+    let width = desc.nbits;
+    if storage.bits() > width as u64 {
+        return Err(FormatError::StorageTooWide { storage, width });
+    }
+    if storage.is_zero() {
+        // There is only one zero, which we treat as positive.
+        return Ok(ArbFloat::new(FloatKind::Zero, BigInt::one()));
+    }
+    if storage == desc.nar() {
+        return Ok(ArbFloat::new(FloatKind::QUIET_NAN, BigInt::one()));
+    }
+    let negative = storage.bit(width as u64 - 1);
+    let bits = if negative {
+        desc.negate(storage)
+    } else {
+        storage
+    };
+
+    // The regime is a run of identical bits right after the sign, ended by
+    // the opposite bit or by the end of the encoding.
+    let regime_bit = bits.bit(width as u64 - 2);
+    let run = (0..width - 1)
+        .take_while(|i| bits.bit((width - 2 - i) as u64) == regime_bit)
+        .count() as u32;
+    let k = if regime_bit {
+        run as i32 - 1
+    } else {
+        -(run as i32)
+    };
+
+    // Whatever the regime leaves over holds the exponent, then the fraction.
+    // Exponent bits cut off by the end of the encoding are zeros.
+    let remaining = (width - 1).saturating_sub(run + 1);
+    let rest = bits & FormatDesc::mask(remaining);
+    let exp_bits = remaining.min(desc.es);
+    let frac_bits = remaining - exp_bits;
+    let e = ((&rest >> frac_bits).to_u32().unwrap() << (desc.es - exp_bits)) as i32;
+    let frac = rest & FormatDesc::mask(frac_bits);
+
+    let scale = (k << desc.es) + e;
+    let num = BigInt::from((IntStorage::one() << frac_bits) | frac);
+    let sign = if negative { -1 } else { 1 };
+    Ok(ArbFloat::new(
+        FloatKind::Regular {
+            exp: scale - frac_bits as i32,
+        },
+        sign * num,
+    ))
+}
+
+// Rounds to the nearest posit, ties to even, as the posit standard requires.
+// The rounding applies to the encoding as a bit string, so where the regime
+// leaves no room for the fraction, it happens in the exponent or the regime
+// itself. Nonzero values never round to zero, and finite values never round
+// to NaR: they saturate at the smallest and largest posits instead. There are
+// no status flags to raise.
+pub fn encode_posit(desc: PositDesc, value: &ArbFloat) -> IntStorage {
+    let exp = match value.kind {
+        FloatKind::Zero => return IntStorage::zero(),
+        FloatKind::Infinity | FloatKind::NaN { .. } => return desc.nar(),
+        FloatKind::Regular { exp } => exp,
+    };
+    let num = value.num.magnitude();
+    let scale = exp + num.bits() as i32 - 1;
+    let keep = desc.nbits - 1;
+
+    let bits = if scale >= desc.max_scale() {
+        FormatDesc::mask(keep)
+    } else if scale < -desc.max_scale() {
+        IntStorage::one()
+    } else {
+        let k = scale >> desc.es;
+        let e = scale & ((1 << desc.es) - 1);
+        let (regime, regime_bits) = if k >= 0 {
+            (FormatDesc::mask(k as u32 + 1) << 1u32, k as u32 + 2)
+        } else {
+            (IntStorage::one(), (-k) as u32 + 1)
+        };
+        // Regime, exponent and fraction, with as many bits as it takes to
+        // hold the value exactly.
+        let frac_bits = num.bits() as u32 - 1;
+        let frac = num & FormatDesc::mask(frac_bits);
+        let string = (((regime << desc.es) | IntStorage::from(e as u32)) << frac_bits) | frac;
+        let string_bits = regime_bits + desc.es + frac_bits;
+
+        if string_bits <= keep {
+            string << (keep - string_bits)
+        } else {
+            let dropped = string_bits - keep;
+            let kept = &string >> dropped;
+            let half = string.bit(dropped as u64 - 1);
+            let sticky = !(&string & FormatDesc::mask(dropped - 1)).is_zero();
+            let rounded = if half && (sticky || kept.bit(0)) {
+                kept + 1u32
+            } else {
+                kept
+            };
+            // The largest posit has a regime of all ones, which is out of
+            // reach here, so rounding up never spills into NaR. It may leave
+            // nothing but zeros though.
+            rounded.max(IntStorage::one())
+        }
+    };
+    if value.num.is_negative() {
+        desc.negate(bits)
+    } else {
+        bits
+    }
+}
+
+// An exact accumulator for sums of products of posits, which is what makes
+// fused dot products possible. Unlike a quire register of fixed width, this one
+// can't overflow.
+#[derive(Debug, Clone)]
+pub struct Quire {
+    desc: PositDesc,
+    sum: ArbFloat,
+}
+
+impl Quire {
+    pub fn new(desc: PositDesc) -> Self {
+        Self {
+            desc,
+            sum: ArbFloat::new(FloatKind::Zero, BigInt::one()),
+        }
+    }
+
+    // Adds `lhs * rhs` to the sum, without rounding. Once a NaR is involved,
+    // the sum stays NaR.
+    pub fn add_product(&mut self, lhs: &ArbFloat, rhs: &ArbFloat) {
+        // Posit arithmetic doesn't have status flags, nor rounding
+        // attributes: there's nothing to keep in this context.
+        let mut ctx = Context::default();
+        let product = lhs.mul_exact(rhs, &mut ctx);
+        self.sum = self.sum.add_exact(&product, &mut ctx);
+    }
+
+    pub fn sub_product(&mut self, lhs: &ArbFloat, rhs: &ArbFloat) {
+        self.add_product(&-lhs, rhs);
+    }
+
+    // Rounds the sum to a posit of the quire's format.
+    pub fn to_posit(&self) -> IntStorage {
+        encode_posit(self.desc, &self.sum)
+    }
+}